@@ -0,0 +1,562 @@
+use alloc::{borrow::Cow, string::String};
+
+use crate::parse::{CommentPolicy, Diagnostic, Entry, Quote, Reason};
+
+/// The rules of the tool an env file is written for, see `Loader::dialect`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Dialect {
+    /// The rules of this crate, described in the `simple_env_load` docs
+    #[default]
+    Dotenv,
+    /// systemd's `EnvironmentFile=`
+    ///
+    /// - lines starting with `#` or `;` are comments, there are no inline comments
+    /// - a `\` at the end of a line continues the value on the next line, in comments too
+    /// - outside of quotes, `\` escapes any character
+    /// - in double quotes, `\` only escapes `"`, `\`, `` ` `` and `$`
+    /// - quoted and unquoted parts of a value are joined, e.g. `'a b'"c"` is `a bc`
+    /// - `$` is literal, values are never expanded
+    Systemd,
+    /// docker-compose's `env_file`, which is also `docker run --env-file`
+    ///
+    /// - lines starting with `#` are comments, there are no inline comments
+    /// - the value is everything after the `=`, quotes and whitespace included
+    /// - a line with just a key passes the variable through from the environment, so it's skipped
+    /// - keys can't have whitespace in them
+    /// - `$` is literal, values are never expanded
+    DockerCompose,
+    /// A POSIX shell script that only has assignments, e.g. one that's `source`d
+    ///
+    /// - lines can start with `export`, keys can't have whitespace in them
+    /// - `#` starts a comment at the start of a line or after whitespace, `A=b#c` is `b#c`
+    /// - outside of quotes, `\` escapes any character and unquoted whitespace ends the value
+    /// - single quotes are literal, in double quotes `\` only escapes `"`, `\`, `` ` ``, `$` and line breaks
+    /// - quoted and unquoted parts of a value are joined, e.g. `'$a'"$b"c` is `$a` followed by `$b`'s value and `c`
+    /// - `$VAR`, `${VAR}` and their modifiers are expanded outside of single quotes
+    Posix,
+    /// Ruby's `dotenv` gem
+    ///
+    /// The same as [`Dialect::Dotenv`], but a key can also be separated from its value by a `:` and whitespace,
+    /// e.g. `PORT: 8080`. Like the gem, a `#` starts a comment anywhere in an unquoted value, `A=b#c` is `b`.
+    Ruby,
+    /// Docker Compose v2's `.env` and `env_file`, see [`Dialect::DockerCompose`] for `docker run --env-file`
+    ///
+    /// The same as [`Dialect::Dotenv`], but a `#` only starts a comment after whitespace, `A=b#c` is `b#c`
+    /// and `A=b #c` is `b`, see [`CommentPolicy::AfterWhitespace`].
+    /// A line with just a key passes the variable through from the environment, so it's skipped.
+    Compose,
+}
+
+impl Dialect {
+    /// Whether `$VAR` references in values are expanded
+    pub fn expands(self) -> bool {
+        matches!(
+            self,
+            Self::Dotenv | Self::Posix | Self::Ruby | Self::Compose
+        )
+    }
+
+    /// Where a `#` starts a comment in an unquoted value, unless it's configured with `Loader::comment_policy`
+    pub fn comments(self) -> CommentPolicy {
+        match self {
+            Self::Compose => CommentPolicy::AfterWhitespace,
+            _ => CommentPolicy::Anywhere,
+        }
+    }
+}
+
+/// Parses the systemd entry starting at the beginning of `input`, returning the unparsed remainder
+pub fn parse_systemd(input: &str) -> (Result<Option<Entry<'_>>, Diagnostic>, &str) {
+    let trimmed = input.trim_start_matches([' ', '\t']);
+    let error = |at: &str, reason| Diagnostic {
+        line: 0,
+        column: input[..at.as_ptr() as usize - input.as_ptr() as usize]
+            .chars()
+            .count()
+            + 1,
+        reason,
+    };
+
+    if trimmed.starts_with(['#', ';']) {
+        let mut chars = trimmed.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => drop(chars.next()),
+                '\n' => return (Ok(None), &trimmed[i + 1..]),
+                _ => {}
+            }
+        }
+        return (Ok(None), "");
+    }
+
+    let line_end = trimmed.find('\n').unwrap_or(trimmed.len());
+    let line = trimmed[..line_end].trim_end_matches('\r');
+    if line.trim().is_empty() {
+        return (Ok(None), trimmed.get(line_end + 1..).unwrap_or_default());
+    }
+    let Some((key, _)) = line.split_once('=') else {
+        let rest = trimmed.get(line_end + 1..).unwrap_or_default();
+        return (Err(error(trimmed, Reason::MissingEquals)), rest);
+    };
+    if key.trim().is_empty() {
+        let rest = trimmed.get(line_end + 1..).unwrap_or_default();
+        return (Err(error(key, Reason::EmptyKey)), rest);
+    }
+
+    let body = trimmed[key.len() + 1..].trim_start_matches([' ', '\t']);
+    let (mut value, mut quote, mut first_close) = (String::new(), None, None);
+    let (mut end, mut last) = (body.len(), 0);
+    let mut chars = body.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (None, '\n') => {
+                end = i;
+                break;
+            }
+            (None, '\'' | '"') => {
+                quote = Some(if c == '\'' {
+                    Quote::Single
+                } else {
+                    Quote::Double
+                })
+            }
+            (None, '\\') => match chars.next() {
+                Some((_, '\n')) => {}
+                Some((_, c)) => {
+                    value.push(c);
+                    last = value.len();
+                }
+                None => {}
+            },
+            (None, c) => {
+                value.push(c);
+                if !c.is_whitespace() {
+                    last = value.len();
+                }
+            }
+            (Some(Quote::Single), '\'') | (Some(Quote::Double), '"') => {
+                quote = None;
+                last = value.len();
+                first_close.get_or_insert(i);
+            }
+            (Some(Quote::Double), '\\') => match chars.next() {
+                Some((_, '\n')) => {}
+                Some((_, c @ ('"' | '\\' | '`' | '$'))) => value.push(c),
+                Some((_, c)) => {
+                    value.push('\\');
+                    value.push(c);
+                }
+                None => value.push('\\'),
+            },
+            (Some(..), c) => value.push(c),
+        }
+    }
+    if quote.is_some() {
+        return (Err(error(body, Reason::UnterminatedQuote)), "");
+    }
+    value.truncate(last);
+
+    let rest = body.get(end + 1..).unwrap_or_default();
+    let written = body[..end].trim_end();
+    let (raw, quote) = match written.chars().next() {
+        // a value that's a single quoted part keeps the quotes out of its raw value
+        Some(q @ ('\'' | '"')) if first_close == Some(written.len() - 1) => (
+            &written[1..written.len() - 1],
+            Some(if q == '\'' {
+                Quote::Single
+            } else {
+                Quote::Double
+            }),
+        ),
+        _ => (written, None),
+    };
+    let value = match raw == value {
+        true => Cow::Borrowed(raw),
+        false => Cow::Owned(value),
+    };
+
+    let entry = Entry {
+        key: key.trim(),
+        value,
+        line: 0,
+        span: 0..0,
+        raw,
+        quote,
+    };
+    (Ok(Some(entry)), rest)
+}
+
+/// Parses the docker-compose entry at the beginning of `input`, returning the unparsed remainder
+pub fn parse_compose(input: &str) -> (Result<Option<Entry<'_>>, Diagnostic>, &str) {
+    let (line, rest) = crate::parse::split_line(input);
+    let line = line.trim_start();
+    if line.is_empty() || line.starts_with('#') {
+        return (Ok(None), rest);
+    }
+    let Some((key, value)) = line.split_once('=') else {
+        return (Ok(None), rest);
+    };
+
+    let error = |at: &str, reason| Diagnostic {
+        line: 0,
+        column: input[..at.as_ptr() as usize - input.as_ptr() as usize]
+            .chars()
+            .count()
+            + 1,
+        reason,
+    };
+    if key.is_empty() {
+        return (Err(error(key, Reason::EmptyKey)), rest);
+    }
+    if let Some(pos) = key.find(char::is_whitespace) {
+        return (Err(error(&key[pos..], Reason::WhitespaceInKey)), rest);
+    }
+
+    let entry = Entry {
+        key,
+        value: Cow::Borrowed(value),
+        line: 0,
+        span: 0..0,
+        raw: value,
+        quote: None,
+    };
+    (Ok(Some(entry)), rest)
+}
+
+/// Parses the POSIX shell assignment at the beginning of `input`, returning the unparsed remainder
+///
+/// Only the first assignment of a line is parsed, the rest of it is ignored.
+pub fn parse_posix(input: &str) -> (Result<Option<Entry<'_>>, Diagnostic>, &str) {
+    let (line, rest) = crate::parse::split_line(input);
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return (Ok(None), rest);
+    }
+
+    let error = |at: &str, reason| Diagnostic {
+        line: 0,
+        column: input[..at.as_ptr() as usize - input.as_ptr() as usize]
+            .chars()
+            .count()
+            + 1,
+        reason,
+    };
+    let assignment = trimmed
+        .strip_prefix("export")
+        .filter(|s| s.starts_with([' ', '\t']))
+        .map_or(trimmed, str::trim_start);
+    let Some((key, _)) = assignment.split_once('=') else {
+        return (Err(error(trimmed, Reason::MissingEquals)), rest);
+    };
+    if key.is_empty() {
+        return (Err(error(key, Reason::EmptyKey)), rest);
+    }
+    if let Some(pos) = key.find(char::is_whitespace) {
+        return (Err(error(&key[pos..], Reason::WhitespaceInKey)), rest);
+    }
+
+    // quoted values can span lines, so the word is read from the input rather than the line
+    let start = key.as_ptr() as usize - input.as_ptr() as usize + key.len() + 1;
+    let body = &input[start..];
+    let (len, value) = match shell_word(body) {
+        Ok((len, value, _)) => (len, value),
+        Err(at) => return (Err(error(&body[at..], Reason::UnterminatedQuote)), rest),
+    };
+    let written = &body[..len];
+    let (_, rest) = crate::parse::split_line(&body[len..]);
+
+    let single =
+        written.len() >= 2 && written.starts_with('\'') && written[1..].find('\'') == Some(len - 2);
+    let (raw, quote) = match () {
+        _ if single => (&written[1..len - 1], Some(Quote::Single)),
+        _ if written == value => (written, None),
+        _ => (written, Some(Quote::Shell)),
+    };
+    let value = match raw == value {
+        true => Cow::Borrowed(raw),
+        false => Cow::Owned(value),
+    };
+
+    let entry = Entry {
+        key,
+        value,
+        line: 0,
+        span: 0..0,
+        raw,
+        quote,
+    };
+    (Ok(Some(entry)), rest)
+}
+
+/// Reads the shell word at the start of `input`
+///
+/// Produces its length, its value, and the value as a template for [`expand`](crate::interpolate::expand)
+/// with escapes, where the literal parts have their `\` and `$` escaped. An unterminated quote is the
+/// offset of the quote.
+pub fn shell_word(input: &str) -> Result<(usize, String, String), usize> {
+    fn literal(c: char, value: &mut String, template: &mut String) {
+        value.push(c);
+        if matches!(c, '\\' | '$') {
+            template.push('\\');
+        }
+        template.push(c);
+    }
+
+    let (mut value, mut template) = (String::new(), String::new());
+
+    let (mut chars, mut double) = (input.char_indices().peekable(), None);
+    while let Some((i, c)) = chars.next() {
+        match (double, c) {
+            (None, c) if c.is_whitespace() => return Ok((i, value, template)),
+            (None, '\'') => {
+                let end = input[i + 1..].find('\'').ok_or(i)?;
+                for c in input[i + 1..i + 1 + end].chars() {
+                    literal(c, &mut value, &mut template);
+                }
+                while chars.next_if(|&(j, _)| j <= i + 1 + end).is_some() {}
+            }
+            (None, '"') => double = Some(i),
+            (Some(_), '"') => double = None,
+            (None, '\\') => match chars.next() {
+                Some((_, '\n')) => {}
+                Some((_, c)) => literal(c, &mut value, &mut template),
+                None => literal('\\', &mut value, &mut template),
+            },
+            (Some(_), '\\') => match chars.peek() {
+                Some((_, '\n')) => drop(chars.next()),
+                Some(&(_, c @ ('"' | '\\' | '`' | '$'))) => {
+                    chars.next();
+                    literal(c, &mut value, &mut template)
+                }
+                _ => literal('\\', &mut value, &mut template),
+            },
+            (_, '$') => {
+                // a `${...}` or `$(...)` is kept whole, quotes and whitespace included
+                let close = match chars.peek() {
+                    Some((_, '{')) => '}',
+                    Some((_, '(')) => ')',
+                    _ => '$',
+                };
+                value.push('$');
+                template.push('$');
+                if close != '$' {
+                    let open = chars.peek().map(|&(_, c)| c).unwrap_or_default();
+                    let mut depth = 0;
+                    for (_, c) in chars.by_ref() {
+                        value.push(c);
+                        template.push(c);
+                        depth += (c == open) as i32 - (c == close) as i32;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+            }
+            (_, c) => literal(c, &mut value, &mut template),
+        }
+    }
+    match double {
+        Some(at) => Err(at),
+        None => Ok((input.len(), value, template)),
+    }
+}
+
+#[test]
+fn parse_systemd_files() {
+    let data = r#"# a comment \
+continued
+; also a comment
+PLAIN = value with spaces
+SINGLE='$HOME "as is"'
+DOUBLE="a\"b\\c\n\$d"
+JOINED='a b'"c"d
+CONTINUED=line 1 \
+line 2
+ESCAPED=\#not\ a\ comment # still the value
+EMPTY=
+"#;
+    let options = crate::parse::Options {
+        dialect: Dialect::Systemd,
+        ..Default::default()
+    };
+    let pairs = crate::parse::parse(data, options)
+        .map(|entry| (entry.key, entry.value.into_owned(), entry.line))
+        .collect::<Vec<_>>();
+    let expected = [
+        ("PLAIN", "value with spaces", 4),
+        ("SINGLE", "$HOME \"as is\"", 5),
+        ("DOUBLE", "a\"b\\c\\n$d", 6),
+        ("JOINED", "a bcd", 7),
+        ("CONTINUED", "line 1 line 2", 8),
+        ("ESCAPED", "#not a comment # still the value", 10),
+        ("EMPTY", "", 11),
+    ];
+    let expected = expected.map(|(k, v, line)| (k, v.to_string(), line));
+    assert_eq!(pairs, expected);
+
+    let lines = crate::parse::parse_lines("A='unterminated\nB=1", options).collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(
+        lines[0].as_ref().unwrap_err().reason,
+        Reason::UnterminatedQuote
+    );
+}
+
+#[test]
+fn parse_compose_files() {
+    let data = "  # comment\nQUOTED=\"kept\" # and this\nSPACED= padded \nPASSED\n\nHASH=a#b\nEMPTY=\nBAD KEY=1\n=2";
+    let options = crate::parse::Options {
+        dialect: Dialect::DockerCompose,
+        ..Default::default()
+    };
+    let lines = crate::parse::parse_lines(data, options)
+        .map(|line| match line {
+            Ok(entry) => Ok((entry.key, entry.value.into_owned(), entry.line)),
+            Err(diagnostic) => Err((diagnostic.reason, diagnostic.line, diagnostic.column)),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            Ok(("QUOTED", "\"kept\" # and this".to_string(), 2)),
+            Ok(("SPACED", " padded ".to_string(), 3)),
+            Ok(("HASH", "a#b".to_string(), 6)),
+            Ok(("EMPTY", "".to_string(), 7)),
+            Err((Reason::WhitespaceInKey, 8, 4)),
+            Err((Reason::EmptyKey, 9, 1)),
+        ]
+    );
+}
+
+#[test]
+fn parse_hashes_like_each_tool() {
+    let data =
+        "PASS=abc#123\nURL=http://host/#top # the url\nQUOTED=\"#a\" # b\nPASSED\nCOLOR=#fff";
+    let values = |dialect| {
+        let options = crate::parse::Options {
+            dialect,
+            ..Default::default()
+        };
+        let entries = crate::parse::parse(data, options);
+        entries
+            .map(|entry| entry.value.into_owned())
+            .collect::<Vec<_>>()
+    };
+    let dotenv = ["abc", "http://host/", "#a", ""];
+    assert_eq!(values(Dialect::Dotenv), dotenv);
+    assert_eq!(values(Dialect::Ruby), dotenv);
+    assert_eq!(
+        values(Dialect::Compose),
+        ["abc#123", "http://host/#top", "#a", "#fff"]
+    );
+    assert_eq!(
+        values(Dialect::DockerCompose),
+        [
+            "abc#123",
+            "http://host/#top # the url",
+            "\"#a\" # b",
+            "#fff"
+        ]
+    );
+
+    let options = crate::parse::Options {
+        dialect: Dialect::Compose,
+        comments: Some(CommentPolicy::Anywhere),
+        ..Default::default()
+    };
+    let entry = crate::parse::parse(data, options).next().unwrap();
+    assert_eq!(entry.value, "abc");
+}
+
+#[test]
+fn parse_posix_files() {
+    let data = r#"#!/bin/sh
+export NAME=app # the name
+SINGLE='$NAME "as is"'
+DOUBLE="$NAME \"quoted\" \n \$ \\"
+JOINED='$NAME'"-$NAME"-\$x\ y
+HASH=a#b
+DEFAULT=${UNSET_POSIX_VAR:-a b}
+MULTI="line 1
+line 2"
+ESCAPED=a\
+b
+BAD KEY=1
+UNTERMINATED="a
+"#;
+    let options = crate::parse::Options {
+        dialect: Dialect::Posix,
+        ..Default::default()
+    };
+    let lines = crate::parse::parse_lines(data, options).collect::<Vec<_>>();
+    let entries = lines.iter().flatten().cloned().collect::<Vec<_>>();
+    let values = entries
+        .iter()
+        .map(|entry| (entry.key, &*entry.value, entry.line));
+    assert_eq!(
+        values.collect::<Vec<_>>(),
+        [
+            ("NAME", "app", 2),
+            ("SINGLE", "$NAME \"as is\"", 3),
+            ("DOUBLE", "$NAME \"quoted\" \\n $ \\", 4),
+            ("JOINED", "$NAME-$NAME-$x y", 5),
+            ("HASH", "a#b", 6),
+            ("DEFAULT", "${UNSET_POSIX_VAR:-a b}", 7),
+            ("MULTI", "line 1\nline 2", 8),
+            ("ESCAPED", "ab", 10),
+        ]
+    );
+    let errors = lines.iter().filter_map(|line| line.as_ref().err());
+    let errors = errors.map(|diagnostic| (diagnostic.reason, diagnostic.line));
+    assert_eq!(
+        errors.collect::<Vec<_>>(),
+        [
+            (Reason::WhitespaceInKey, 12),
+            (Reason::UnterminatedQuote, 13)
+        ]
+    );
+
+    let resolved = crate::interpolate::resolve(&entries, &|_| None)
+        .into_iter()
+        .map(|pair| pair.unwrap().1)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        resolved,
+        [
+            "app",
+            "$NAME \"as is\"",
+            "app \"quoted\" \\n $ \\",
+            "$NAME-app-$x y",
+            "a#b",
+            "a b",
+            "line 1\nline 2",
+            "ab",
+        ]
+    );
+}
+
+#[test]
+fn parse_ruby_files() {
+    let data =
+        "export PORT: 8080\nHOST=localhost # comment\nURL: \"http://$HOST:$PORT\"\nTIME=12:30";
+    let options = crate::parse::Options {
+        dialect: Dialect::Ruby,
+        ..Default::default()
+    };
+    let entries = crate::parse::parse(data, options).collect::<Vec<_>>();
+    let resolved = crate::interpolate::resolve(&entries, &|_| None)
+        .into_iter()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    let expected = [
+        ("PORT", "8080"),
+        ("HOST", "localhost"),
+        ("URL", "http://localhost:8080"),
+        ("TIME", "12:30"),
+    ];
+    assert_eq!(
+        resolved,
+        expected.map(|(k, v)| (k.to_string(), v.to_string()))
+    );
+}
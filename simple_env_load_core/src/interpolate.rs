@@ -0,0 +1,546 @@
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::parse::{self, Entry, Options, Quote};
+
+/// An error produced while expanding `$VAR` and `${VAR}` references
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExpandError {
+    /// These variables reference each other, in the order they were visited
+    Cycle(Vec<String>),
+    /// A variable required by `${VAR:?message}` isn't set
+    Unset {
+        /// The name of the variable
+        key: String,
+        /// The message after the `?`, which can be empty
+        message: String,
+    },
+    /// A `$(command)` failed, see `Loader::commands`
+    Command {
+        /// The command line
+        command: String,
+        /// Why it failed
+        error: String,
+    },
+    /// A value expands to more than `Limits::max_value_length` allows
+    TooLong {
+        /// The longest a value can be, in bytes
+        max: usize,
+    },
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle(keys) => write!(f, "variables reference each other: {}", keys.join(" -> ")),
+            Self::Unset { key, message } if message.is_empty() => write!(f, "'{key}' is not set"),
+            Self::Unset { key, message } => write!(f, "'{key}' is not set: {message}"),
+            Self::Command { command, error } => write!(f, "'$({command})' failed: {error}"),
+            Self::TooLong { max } => write!(f, "a value expands to more than {max} bytes"),
+        }
+    }
+}
+
+impl core::error::Error for ExpandError {}
+
+/// What a `$` refers to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Ref<'a> {
+    /// `$VAR` or `${VAR}`
+    Var(&'a str),
+    /// `$(command)`, with the variables in the command expanded
+    Command(&'a str),
+}
+
+/// Expands `$VAR`, `${VAR}` and `$(command)` in `input` with `lookup`, `\$` is a literal `$`
+///
+/// `lookup` returns `None` for unset variables, which `${VAR:-default}` and `${VAR:?message}` act on.
+/// A command that `lookup` returns `None` for is left as is.
+/// Other references to unset variables are empty, or kept as they're written with `keep_unset`.
+/// With `escapes`, the rest of the double quote escapes are interpreted too.
+pub fn expand<E: From<ExpandError>>(
+    input: &str,
+    escapes: bool,
+    keep_unset: bool,
+    mut lookup: impl FnMut(Ref<'_>) -> Result<Option<String>, E>,
+) -> Result<String, E> {
+    expand_with(input, escapes, keep_unset, false, usize::MAX, &mut lookup)
+}
+
+type Lookup<'a, E> = dyn FnMut(Ref<'_>) -> Result<Option<String>, E> + 'a;
+
+/// Like [`expand`], also expanding `%VAR%` with `percent`
+///
+/// Like `cmd`, a `%VAR%` of an unset variable is kept as is.
+/// Expansion stops as soon as the value is longer than `max` bytes, so references can't multiply it without bound.
+fn expand_with<E: From<ExpandError>>(
+    input: &str,
+    escapes: bool,
+    keep_unset: bool,
+    percent: bool,
+    max: usize,
+    lookup: &mut Lookup<'_, E>,
+) -> Result<String, E> {
+    let too_long = |out: &String| match out.len() > max {
+        true => Err(E::from(ExpandError::TooLong { max })),
+        false => Ok(()),
+    };
+    let specials: &[char] = match percent {
+        true => &['$', '\\', '%'],
+        false => &['$', '\\'],
+    };
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find(specials) {
+        too_long(&out)?;
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos + 1..];
+
+        if rest[pos..].starts_with('%') {
+            let name = tail.find('%').map(|end| &tail[..end]).filter(|name| {
+                !name.is_empty() && !name.contains(|c: char| c == '=' || c.is_whitespace())
+            });
+            rest = match name {
+                Some(name) => {
+                    match lookup(Ref::Var(name))? {
+                        Some(value) => out.push_str(&value),
+                        None => out.push_str(&rest[pos..pos + name.len() + 2]),
+                    }
+                    &tail[name.len() + 1..]
+                }
+                None => {
+                    out.push('%');
+                    tail
+                }
+            };
+            continue;
+        }
+
+        if rest[pos..].starts_with('\\') {
+            rest = match tail.strip_prefix('$') {
+                Some(tail) => {
+                    out.push('$');
+                    tail
+                }
+                None if escapes => parse::unescape_one(tail, &mut out),
+                None => {
+                    out.push('\\');
+                    tail
+                }
+            };
+            continue;
+        }
+
+        if let Some(len) = command(tail) {
+            let line = expand_with(&tail[1..len - 1], escapes, keep_unset, percent, max, lookup)?;
+            match lookup(Ref::Command(&line))? {
+                Some(output) => out.push_str(&output),
+                None => out.push_str(&rest[pos..pos + 1 + len]),
+            }
+            rest = &tail[len..];
+            continue;
+        }
+
+        let reference = match reference(tail) {
+            Some(reference) => reference,
+            None => {
+                out.push('$');
+                rest = tail;
+                continue;
+            }
+        };
+
+        let value = lookup(Ref::Var(reference.name))?;
+        let unset = match reference.modifier {
+            Some(Modifier { colon: true, .. }) => value.as_deref().is_none_or(str::is_empty),
+            _ => value.is_none(),
+        };
+        match (reference.modifier, value) {
+            (
+                Some(Modifier {
+                    default: true,
+                    word,
+                    ..
+                }),
+                _,
+            ) if unset => out.push_str(&expand_with(
+                word, escapes, keep_unset, percent, max, lookup,
+            )?),
+            (
+                Some(Modifier {
+                    default: false,
+                    word,
+                    ..
+                }),
+                _,
+            ) if unset => {
+                return Err(E::from(ExpandError::Unset {
+                    key: reference.name.to_string(),
+                    message: expand_with(word, escapes, keep_unset, percent, max, lookup)?,
+                }))
+            }
+            (_, Some(value)) => out.push_str(&value),
+            (_, None) if keep_unset => out.push_str(&rest[pos..pos + 1 + reference.len]),
+            (_, None) => {}
+        }
+        rest = &tail[reference.len..];
+    }
+    out.push_str(rest);
+    too_long(&out)?;
+    Ok(out)
+}
+
+/// How long the `(command)` following a `$` is, with its parentheses
+fn command(tail: &str) -> Option<usize> {
+    let mut depth = 0;
+    tail.strip_prefix('(')?;
+    tail.char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 1 => return Some(i + 1),
+            ')' => depth -= 1,
+            _ => {}
+        }
+        None
+    })
+}
+
+/// A reference following a `$`
+pub struct Reference<'a> {
+    pub name: &'a str,
+    pub modifier: Option<Modifier<'a>>,
+    /// How long the reference is, without the `$`
+    pub len: usize,
+}
+
+/// The `:-word`, `-word`, `:?word` or `?word` after the name of a braced reference
+#[derive(Copy, Clone)]
+pub struct Modifier<'a> {
+    /// Whether an empty variable is treated like an unset one
+    colon: bool,
+    /// Whether this is a default (`-`) rather than an error (`?`)
+    default: bool,
+    word: &'a str,
+}
+
+/// Parses the reference following a `$`
+pub fn reference(tail: &str) -> Option<Reference<'_>> {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let braced = match tail.strip_prefix('{') {
+        Some(braced) => braced,
+        None => {
+            if !tail.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                return None;
+            }
+            let end = tail.find(|c: char| !is_name(c)).unwrap_or(tail.len());
+            let name = &tail[..end];
+            return Some(Reference {
+                name,
+                modifier: None,
+                len: end,
+            });
+        }
+    };
+
+    let end = braced.find(|c: char| !is_name(c)).unwrap_or(braced.len());
+    let (name, after) = braced.split_at(end);
+    let (colon, after) = match after.strip_prefix(':') {
+        Some(after) => (true, after),
+        None => (false, after),
+    };
+    let default = match after.chars().next() {
+        Some('-') => true,
+        Some('?') => false,
+        _ => {
+            let name = &braced[..braced.find('}')?];
+            return Some(Reference {
+                name,
+                modifier: None,
+                len: name.len() + 2,
+            });
+        }
+    };
+
+    let word = &after[1..];
+    let mut depth = 0;
+    let close = word.char_indices().find_map(|(i, c)| {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => {}
+        }
+        None
+    })?;
+
+    let word = &word[..close];
+    Some(Reference {
+        name,
+        modifier: Some(Modifier {
+            colon,
+            default,
+            word,
+        }),
+        len: 1 + end + colon as usize + 1 + word.len() + 1,
+    })
+}
+
+/// Looks up a variable that isn't defined in the pairs, e.g. in the process environment
+pub type Env = dyn Fn(&str) -> Option<String>;
+
+/// Expands the references in each value against the other pairs, then `env`
+///
+/// References resolve to the last definition of a key. A key referencing itself
+/// (e.g. `PATH=$PATH:/bin`) resolves to its previous definition, like in a shell,
+/// or to its value in `env` if it's the first.
+/// Single quoted values are left as is.
+pub fn resolve(pairs: &[Entry<'_>], env: &Env) -> Vec<Result<(String, String), ExpandError>> {
+    resolve_with(pairs, None, Options::default(), env)
+}
+
+/// Like [`resolve`], running `$(command)`s with `run` if there is one
+///
+/// The `options` the pairs were parsed with can replace the line breaks in double quoted values with `\n`,
+/// and expand `%VAR%` references.
+pub fn resolve_with(
+    pairs: &[Entry<'_>],
+    run: Option<&Run>,
+    options: Options,
+    env: &Env,
+) -> Vec<Result<(String, String), ExpandError>> {
+    let mut resolver = Resolver {
+        run,
+        env,
+        options,
+        latest: pairs.iter().enumerate().map(|(i, p)| (p.key, i)).collect(),
+        pairs,
+        cache: BTreeMap::new(),
+        stack: vec![],
+    };
+
+    (0..pairs.len())
+        .map(|i| resolver.resolve(i).map(|v| (pairs[i].key.to_string(), v)))
+        .collect()
+}
+
+/// Runs a command line, producing its output or why it failed
+pub type Run = dyn Fn(&str) -> Result<String, String> + Send + Sync;
+
+struct Resolver<'a, 'p> {
+    run: Option<&'p Run>,
+    env: &'p Env,
+    options: Options,
+    pairs: &'p [Entry<'a>],
+    latest: BTreeMap<&'a str, usize>,
+    cache: BTreeMap<usize, Result<String, ExpandError>>,
+    stack: Vec<usize>,
+}
+
+impl Resolver<'_, '_> {
+    fn resolve(&mut self, index: usize) -> Result<String, ExpandError> {
+        if let Some(value) = self.cache.get(&index) {
+            return value.clone();
+        }
+
+        if let Some(pos) = self.stack.iter().position(|&i| i == index) {
+            let keys = self.stack[pos..].iter().chain(Some(&index));
+            let keys = keys.map(|&i| self.pairs[i].key.to_string()).collect();
+            return Err(ExpandError::Cycle(keys));
+        }
+
+        let pairs = self.pairs;
+        let pair = &pairs[index];
+        if matches!(
+            pair.quote,
+            Some(Quote::Single | Quote::Heredoc { expand: false })
+        ) {
+            return Ok(pair.value.to_string());
+        }
+
+        self.stack.push(index);
+        let escapes = pair.quote == Some(Quote::Double);
+        let options = self.options;
+        let Options {
+            percent_references: percent,
+            normalize_newlines,
+            max_value_length,
+            ..
+        } = options;
+        let max = max_value_length.unwrap_or(usize::MAX);
+        let mut lookup = |name: Ref<'_>| self.lookup(index, name);
+        let value = match pair.quote {
+            Some(Quote::Shell) => {
+                let word = crate::dialect::shell_word(pair.raw);
+                let (_, _, template) = word.expect("the value was parsed");
+                expand_with(&template, true, false, percent, max, &mut lookup)
+            }
+            Some(Quote::Joined) => {
+                let template = parse::template(pair.raw, options);
+                expand_with(&template, true, false, percent, max, &mut lookup)
+            }
+            Some(Quote::Double | Quote::Heredoc { .. })
+                if normalize_newlines && parse::has_line_breaks(pair.raw) =>
+            {
+                let raw = parse::normalize_newlines(pair.raw);
+                expand_with(&raw, escapes, false, percent, max, &mut lookup)
+            }
+            _ => expand_with(pair.raw, escapes, false, percent, max, &mut lookup),
+        };
+        self.stack.pop();
+
+        self.cache.insert(index, value.clone());
+        value
+    }
+
+    /// Looks up a reference in the value of the pair at `index`
+    fn lookup(&mut self, index: usize, reference: Ref<'_>) -> Result<Option<String>, ExpandError> {
+        let name = match (reference, self.run) {
+            (Ref::Var(name), _) => name,
+            (Ref::Command(command), Some(run)) => {
+                return run(command)
+                    .map(Some)
+                    .map_err(|error| ExpandError::Command {
+                        command: command.to_string(),
+                        error,
+                    })
+            }
+            (Ref::Command(..), None) => return Ok(None),
+        };
+
+        let upper;
+        let defined = match self.options.case_insensitive {
+            true => {
+                upper = name.to_uppercase();
+                &upper
+            }
+            false => name,
+        };
+        // a key referencing itself refers to its previous definition
+        let pairs = self.pairs;
+        let definition = match self.latest.get(defined) {
+            Some(&latest) if defined != pairs[index].key => Some(latest),
+            Some(..) => pairs[..index].iter().rposition(|p| p.key == defined),
+            None => None,
+        };
+        match definition {
+            Some(definition) => self.resolve(definition).map(Some),
+            None => Ok((self.env)(name)),
+        }
+    }
+}
+
+#[test]
+fn expand_references() {
+    let data = r#"
+    SIMPLE_ENV_LOAD_HOST=localhost
+    SIMPLE_ENV_LOAD_URL=postgres://$SIMPLE_ENV_LOAD_HOST/${SIMPLE_ENV_LOAD_DB}
+    SIMPLE_ENV_LOAD_DB=app
+    SIMPLE_ENV_LOAD_LITERAL='$SIMPLE_ENV_LOAD_HOST'
+    SIMPLE_ENV_LOAD_PRICE=5$
+    SIMPLE_ENV_LOAD_ESCAPED="\$SIMPLE_ENV_LOAD_HOST\t\\$SIMPLE_ENV_LOAD_DB"
+    SIMPLE_ENV_LOAD_DEFAULT=${SIMPLE_ENV_LOAD_UNSET:-${SIMPLE_ENV_LOAD_EMPTY:-fallback}}
+    SIMPLE_ENV_LOAD_EMPTY=
+    SIMPLE_ENV_LOAD_NO_COLON=${SIMPLE_ENV_LOAD_EMPTY-fallback}
+    SIMPLE_ENV_LOAD_REQUIRED="${SIMPLE_ENV_LOAD_UNSET:?set it in $SIMPLE_ENV_LOAD_DB}"
+    SIMPLE_ENV_LOAD_A=$SIMPLE_ENV_LOAD_B
+    SIMPLE_ENV_LOAD_B=${SIMPLE_ENV_LOAD_A}
+    SIMPLE_ENV_LOAD_SELF=1
+    SIMPLE_ENV_LOAD_SELF=$SIMPLE_ENV_LOAD_SELF:2
+    SIMPLE_ENV_LOAD_SELF_REF=$SIMPLE_ENV_LOAD_SELF
+    SIMPLE_ENV_LOAD_SELF=${SIMPLE_ENV_LOAD_SELF}:3
+    "#;
+
+    let pairs = crate::parse::parse(data, Default::default()).collect::<Vec<_>>();
+    let values = resolve(&pairs, &|_| None);
+    assert_eq!(
+        values[..6],
+        [
+            Ok(("SIMPLE_ENV_LOAD_HOST".into(), "localhost".into())),
+            Ok((
+                "SIMPLE_ENV_LOAD_URL".into(),
+                "postgres://localhost/app".into()
+            )),
+            Ok(("SIMPLE_ENV_LOAD_DB".into(), "app".into())),
+            Ok((
+                "SIMPLE_ENV_LOAD_LITERAL".into(),
+                "$SIMPLE_ENV_LOAD_HOST".into()
+            )),
+            Ok(("SIMPLE_ENV_LOAD_PRICE".into(), "5$".into())),
+            Ok((
+                "SIMPLE_ENV_LOAD_ESCAPED".into(),
+                "$SIMPLE_ENV_LOAD_HOST\t\\app".into()
+            )),
+        ]
+    );
+
+    let cycle = ExpandError::Cycle(vec![
+        "SIMPLE_ENV_LOAD_A".into(),
+        "SIMPLE_ENV_LOAD_B".into(),
+        "SIMPLE_ENV_LOAD_A".into(),
+    ]);
+    assert_eq!(
+        values[6..9],
+        [
+            Ok(("SIMPLE_ENV_LOAD_DEFAULT".into(), "fallback".into())),
+            Ok(("SIMPLE_ENV_LOAD_EMPTY".into(), "".into())),
+            Ok(("SIMPLE_ENV_LOAD_NO_COLON".into(), "".into())),
+        ]
+    );
+    let unset = ExpandError::Unset {
+        key: "SIMPLE_ENV_LOAD_UNSET".into(),
+        message: "set it in app".into(),
+    };
+    assert_eq!(values[9], Err(unset));
+    assert_eq!(values[10], Err(cycle));
+    assert_eq!(
+        values[12..],
+        [
+            Ok(("SIMPLE_ENV_LOAD_SELF".into(), "1".into())),
+            Ok(("SIMPLE_ENV_LOAD_SELF".into(), "1:2".into())),
+            Ok(("SIMPLE_ENV_LOAD_SELF_REF".into(), "1:2:3".into())),
+            Ok(("SIMPLE_ENV_LOAD_SELF".into(), "1:2:3".into())),
+        ]
+    );
+}
+
+#[test]
+fn expand_percent_references() {
+    let data = "\
+DIR=C:\\app
+LOGS=%DIR%\\logs
+ENCODED=a%20b%20c 50% off
+UNSET=%SIMPLE_ENV_LOAD_PERCENT_UNSET% %%
+DEFAULT=${SIMPLE_ENV_LOAD_PERCENT_UNSET:-%DIR%}
+SINGLE='%DIR%'
+A=%B%
+B=%A%";
+    let options = Options {
+        percent_references: true,
+        ..Options::default()
+    };
+    let pairs = parse::parse(data, options).collect::<Vec<_>>();
+    let resolved = resolve_with(&pairs, None, options, &|_| None);
+    let values = resolved[..6].iter().map(|r| r.as_ref().unwrap().1.as_str());
+    assert_eq!(
+        values.collect::<Vec<_>>(),
+        [
+            "C:\\app",
+            "C:\\app\\logs",
+            "a%20b%20c 50% off",
+            "%SIMPLE_ENV_LOAD_PERCENT_UNSET% %%",
+            "C:\\app",
+            "%DIR%",
+        ]
+    );
+    assert!(matches!(resolved[6], Err(ExpandError::Cycle(..))));
+
+    let resolved = resolve(&pairs, &|_| None);
+    assert_eq!(resolved[1].as_ref().unwrap().1, "%DIR%\\logs");
+}
@@ -0,0 +1,13 @@
+/*!
+The env file parser shared by [simple_env_load](https://docs.rs/simple_env_load) and its `load_env!` macro,
+so a file reads the same at compile time as it does at runtime.
+
+Use it through `simple_env_load`, this crate has no stable API of its own.
+*/
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod dialect;
+pub mod interpolate;
+pub mod parse;
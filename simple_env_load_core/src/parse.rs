@@ -0,0 +1,1190 @@
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+use core::{fmt, ops::Range};
+
+use crate::dialect::Dialect;
+
+/// The kind of quotes that surrounded a value
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Quote {
+    Single,
+    Double,
+    /// A [`Dialect::Posix`] value with quotes or escapes, its raw value is written with its quotes
+    Shell,
+    /// Adjacent segments, e.g. `"a"'b'`, its raw value is written with its quotes
+    Joined,
+    /// The lines of a `KEY<<EOF` block, which are only expanded if the delimiter isn't quoted
+    Heredoc {
+        expand: bool,
+    },
+}
+
+/// A parsed `key=value` line, borrowed from the input unless the value had escapes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry<'a> {
+    /// The name of the variable
+    pub key: &'a str,
+    /// The value, with quotes removed and escapes interpreted
+    pub value: Cow<'a, str>,
+    /// The 1-based line the entry started on
+    pub line: usize,
+    /// The byte range of the entry in the input, without its line ending
+    pub span: Range<usize>,
+    /// The value as written, without its quotes
+    pub raw: &'a str,
+    pub quote: Option<Quote>,
+}
+
+impl<'a> Entry<'a> {
+    /// The value as written, without its quotes, before escapes and references were interpreted
+    pub fn raw(&self) -> &'a str {
+        self.raw
+    }
+
+    /// Whether the value was surrounded by quotes
+    pub fn is_quoted(&self) -> bool {
+        match self.quote {
+            Some(Quote::Shell) => self.raw.contains(['\'', '"']),
+            Some(Quote::Joined | Quote::Heredoc { .. }) => true,
+            quote => quote.is_some(),
+        }
+    }
+}
+
+/// A line that couldn't be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The 1-based line of the problem
+    pub line: usize,
+    /// The 1-based column of the problem, in characters
+    pub column: usize,
+    /// What went wrong
+    pub reason: Reason,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.reason)
+    }
+}
+
+impl core::error::Error for Diagnostic {}
+
+/// Why a line couldn't be parsed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Reason {
+    /// The line has no `=` between the key and the value
+    MissingEquals,
+    /// A quoted value has no closing quote
+    UnterminatedQuote,
+    /// There is nothing before the `=`
+    EmptyKey,
+    /// The key has whitespace in it, which [`Dialect::DockerCompose`] doesn't allow
+    WhitespaceInKey,
+    /// Something other than a comment follows a quoted value, e.g. `KEY="a" b`
+    TrailingCharacters,
+    /// A `KEY<<EOF` block has no line with just its delimiter
+    UnterminatedHeredoc,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::MissingEquals => "missing '=' after the key",
+            Self::UnterminatedQuote => "unterminated quote",
+            Self::EmptyKey => "empty key",
+            Self::WhitespaceInKey => "whitespace in the key",
+            Self::TrailingCharacters => "unexpected characters after the quoted value",
+            Self::UnterminatedHeredoc => "missing the line that ends the heredoc",
+        })
+    }
+}
+
+/// Where a `#` in an unquoted value starts a comment, see `Loader::comment_policy`
+///
+/// A `#` in quotes never starts a comment. Each [`Dialect`] has its own, [`Dialect::Compose`] only starts
+/// them after whitespace and the others anywhere.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CommentPolicy {
+    /// Anywhere, `PASS=abc#123` is `abc`
+    #[default]
+    Anywhere,
+    /// Only after whitespace, `PASS=abc#123` is `abc#123` but `PASS=abc #123` is `abc`
+    AfterWhitespace,
+    /// Never, `PASS=abc #123` is `abc #123`
+    Never,
+}
+
+/// Which values have the whitespace around them removed, see `Loader::trim_values`
+///
+/// The whitespace between the `=` and a quote, and after the closing quote, is never part of the value.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TrimPolicy {
+    /// Unquoted values, `KEY= a ` is `a` and `KEY="  a  "` is `  a  `
+    #[default]
+    Unquoted,
+    /// Quoted values too, inside of the quotes, `KEY="  a  "` is `a`
+    All,
+    /// Neither, everything after the `=` up to a comment is the value, `KEY= a ` is ` a `
+    Never,
+}
+
+/// Knobs for how lines are parsed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    /// Where a `#` in an unquoted value starts a comment, the dialect's rule if it's `None`
+    pub comments: Option<CommentPolicy>,
+    /// Which values are trimmed
+    pub trim: TrimPolicy,
+    /// Whether surrounding quotes are removed from values
+    pub strip_quotes: bool,
+    /// Whether the line breaks in quoted values become `\n`
+    pub normalize_newlines: bool,
+    /// Whether `%VAR%` references are expanded along with `$VAR`
+    pub percent_references: bool,
+    /// Whether keys are uppercased, so references are compared without case
+    pub case_insensitive: bool,
+    /// The rules of the tool the data is written for
+    pub dialect: Dialect,
+    /// The longest a value can expand to, see `Limits::max_value_length`
+    pub max_value_length: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            comments: None,
+            trim: TrimPolicy::Unquoted,
+            strip_quotes: true,
+            normalize_newlines: false,
+            percent_references: false,
+            case_insensitive: false,
+            dialect: Dialect::Dotenv,
+            max_value_length: None,
+        }
+    }
+}
+
+pub fn parse(data: &str, options: Options) -> impl Iterator<Item = Entry<'_>> + '_ {
+    parse_lines(data, options).filter_map(Result::ok)
+}
+
+/// Parses every line, producing either an entry or a diagnostic for each non-blank, non-comment line
+pub fn parse_lines(
+    data: &str,
+    options: Options,
+) -> impl Iterator<Item = Result<Entry<'_>, Diagnostic>> + '_ {
+    parse_lines_in(data, options, vec![])
+}
+
+/// Like [`parse_lines`], starting inside the `sections` that are open, see [`section`]
+pub fn parse_lines_in(
+    data: &str,
+    options: Options,
+    sections: Vec<bool>,
+) -> impl Iterator<Item = Result<Entry<'_>, Diagnostic>> + '_ {
+    chunks_in(data, options, sections).filter_map(|(_, line)| line.transpose())
+}
+
+/// Splits `data` into the text of each logical line, along with what it parsed as
+///
+/// Concatenating the text of every chunk reproduces `data` exactly.
+/// Lines in an inactive `#if` section parse as nothing.
+pub fn chunks(
+    data: &str,
+    options: Options,
+) -> impl Iterator<Item = (&str, Result<Option<Entry<'_>>, Diagnostic>)> + '_ {
+    chunks_in(data, options, vec![])
+}
+
+/// Like [`chunks`], starting inside the `sections` that are open, see [`section`]
+pub fn chunks_in(
+    data: &str,
+    options: Options,
+    sections: Vec<bool>,
+) -> impl Iterator<Item = (&str, Result<Option<Entry<'_>>, Diagnostic>)> + '_ {
+    let (mut rest, mut cursor) = (data, Cursor::new(options, sections));
+    core::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (chunk, entry, tail) = cursor.next(data, rest);
+        rest = tail;
+        Some((chunk, entry))
+    })
+}
+
+/// The byte order mark some Windows editors start files with
+pub const BOM: char = '\u{feff}';
+
+/// Where [`chunks_in`] is, so the logical lines of some data can be parsed a piece at a time
+pub struct Cursor {
+    options: Options,
+    sections: Vec<bool>,
+    /// The 1-based line the next chunk starts on
+    line: usize,
+    /// Added to the spans, for pieces that don't start at the beginning of the data
+    pub offset: usize,
+}
+
+impl Cursor {
+    pub fn new(options: Options, sections: Vec<bool>) -> Self {
+        Self {
+            options,
+            sections,
+            line: 1,
+            offset: 0,
+        }
+    }
+
+    /// Parses the logical line that `rest`, the unparsed end of `data`, starts with
+    ///
+    /// Produces its text, what it parsed as and the rest after it.
+    pub fn next<'a>(
+        &mut self,
+        data: &'a str,
+        rest: &'a str,
+    ) -> (&'a str, Result<Option<Entry<'a>>, Diagnostic>, &'a str) {
+        // a byte order mark is part of the first chunk, but not of its entry
+        let bom = match rest.as_ptr() == data.as_ptr() && self.offset == 0 && rest.starts_with(BOM)
+        {
+            true => BOM.len_utf8(),
+            false => 0,
+        };
+        let (entry, tail) = parse_line(&rest[bom..], self.options);
+        let chunk = &rest[..rest.len() - tail.len()];
+        let start = self.line;
+        self.line += line_breaks(chunk);
+
+        let sectioned =
+            self.options.dialect == Dialect::Dotenv && section(chunk, &mut self.sections);
+        if sectioned || self.sections.contains(&false) {
+            return (chunk, Ok(None), tail);
+        }
+
+        let offset = self.offset + chunk.as_ptr() as usize - data.as_ptr() as usize + bom;
+        let span = offset..offset + chunk[bom..].trim_end_matches(LINE_BREAKS).len();
+        let entry = entry
+            .map(|entry| {
+                entry.map(|entry| Entry {
+                    line: start,
+                    span,
+                    ..entry
+                })
+            })
+            .map_err(|diagnostic| Diagnostic {
+                line: start,
+                ..diagnostic
+            });
+        (chunk, entry, tail)
+    }
+}
+
+/// Whether `data` starts with a quote that's still open at its end, so a logical line needs more of the data
+pub fn is_open(data: &str, options: Options) -> bool {
+    matches!(
+        parse_line(data, options).0,
+        Err(Diagnostic {
+            reason: Reason::UnterminatedQuote | Reason::UnterminatedHeredoc,
+            ..
+        })
+    )
+}
+
+/// Tracks `#if`, `#else` and `#endif` lines, returning whether `chunk` was one
+///
+/// Each open section is whether its condition held.
+pub fn section(chunk: &str, sections: &mut Vec<bool>) -> bool {
+    let line = chunk.trim();
+    match line {
+        "#else" if !sections.is_empty() => {
+            let last = sections.last_mut().expect("an open section");
+            *last = !*last;
+            true
+        }
+        "#endif" if !sections.is_empty() => {
+            sections.pop();
+            true
+        }
+        _ => match line.strip_prefix("#if ").and_then(condition) {
+            Some(holds) => {
+                sections.push(holds);
+                true
+            }
+            None => false,
+        },
+    }
+}
+
+/// Evaluates `os=linux`, `family!=windows` or `arch=x86_64,aarch64` for the current platform
+fn condition(condition: &str) -> Option<bool> {
+    let (key, negate, values) = match condition.split_once("!=") {
+        Some((key, values)) => (key, true, values),
+        None => {
+            let (key, values) = condition.split_once('=')?;
+            (key, false, values)
+        }
+    };
+
+    let actual = match key.trim() {
+        "os" => consts::OS,
+        "family" => consts::FAMILY,
+        "arch" => consts::ARCH,
+        _ => return None,
+    };
+    let holds = values.split(',').any(|value| value.trim() == actual);
+    Some(holds != negate)
+}
+
+#[cfg(feature = "std")]
+use std::env::consts;
+
+/// The platform the conditions compare against, as `std::env::consts` would describe it
+#[cfg(not(feature = "std"))]
+mod consts {
+    pub const OS: &str = match () {
+        _ if cfg!(target_os = "linux") => "linux",
+        _ if cfg!(target_os = "macos") => "macos",
+        _ if cfg!(target_os = "windows") => "windows",
+        _ if cfg!(target_os = "android") => "android",
+        _ if cfg!(target_os = "ios") => "ios",
+        _ if cfg!(target_os = "freebsd") => "freebsd",
+        _ => "",
+    };
+    pub const FAMILY: &str = match () {
+        _ if cfg!(unix) => "unix",
+        _ if cfg!(windows) => "windows",
+        _ => "",
+    };
+    pub const ARCH: &str = match () {
+        _ if cfg!(target_arch = "x86") => "x86",
+        _ if cfg!(target_arch = "x86_64") => "x86_64",
+        _ if cfg!(target_arch = "arm") => "arm",
+        _ if cfg!(target_arch = "aarch64") => "aarch64",
+        _ if cfg!(target_arch = "riscv32") => "riscv32",
+        _ if cfg!(target_arch = "riscv64") => "riscv64",
+        _ if cfg!(target_arch = "xtensa") => "xtensa",
+        _ if cfg!(target_arch = "wasm32") => "wasm32",
+        _ => "",
+    };
+}
+
+/// What a line can end with, along with `\r\n`
+pub const LINE_BREAKS: [char; 4] = ['\n', '\r', '\u{2028}', '\u{2029}'];
+
+/// Splits off the first line, without its line ending
+///
+/// Lines end with `\n`, `\r\n`, a lone `\r`, or a Unicode line or paragraph separator.
+pub fn split_line(input: &str) -> (&str, &str) {
+    let Some(end) = input.find(LINE_BREAKS) else {
+        return (input, "");
+    };
+    let rest = &input[end..];
+    let len = match rest.starts_with("\r\n") {
+        true => 2,
+        false => rest.chars().next().map_or(0, char::len_utf8),
+    };
+    (&input[..end], &rest[len..])
+}
+
+/// Whether `text` has line breaks other than `\n`
+pub fn has_line_breaks(text: &str) -> bool {
+    text.contains(&LINE_BREAKS[1..])
+}
+
+/// `text` with each of its line breaks replaced by `\n`
+pub fn normalize_newlines(text: &str) -> String {
+    let (mut out, mut rest) = (String::with_capacity(text.len()), text);
+    while !rest.is_empty() {
+        let (line, tail) = split_line(rest);
+        out.push_str(line);
+        if line.len() < rest.len() {
+            out.push('\n');
+        }
+        rest = tail;
+    }
+    out
+}
+
+/// How many line breaks `text` has, see [`split_line`]
+pub fn line_breaks(text: &str) -> usize {
+    text.matches(LINE_BREAKS).count() - text.matches("\r\n").count()
+}
+
+/// Parses the entry starting at the beginning of `input`, returning the unparsed remainder
+///
+/// A quoted value can span several lines, so the remainder starts after its closing quote's line.
+fn parse_line(input: &str, options: Options) -> (Result<Option<Entry<'_>>, Diagnostic>, &str) {
+    match options.dialect {
+        Dialect::Systemd => return crate::dialect::parse_systemd(input),
+        Dialect::DockerCompose => return crate::dialect::parse_compose(input),
+        Dialect::Posix => return crate::dialect::parse_posix(input),
+        Dialect::Dotenv | Dialect::Ruby | Dialect::Compose => {}
+    }
+
+    let (line, rest) = split_line(input);
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return (Ok(None), rest);
+    }
+
+    let error = |at: &str, reason| Diagnostic {
+        line: 0,
+        column: input[..at.as_ptr() as usize - input.as_ptr() as usize]
+            .chars()
+            .count()
+            + 1,
+        reason,
+    };
+
+    if let Some((key, delimiter, expand)) = heredoc(line) {
+        let start = rest.as_ptr() as usize - input.as_ptr() as usize;
+        let mut tail = rest;
+        while !tail.is_empty() {
+            let (text, next) = split_line(tail);
+            if text.trim() != delimiter {
+                tail = next;
+                continue;
+            }
+            let body = &input[start..tail.as_ptr() as usize - input.as_ptr() as usize];
+            let body = body.strip_suffix("\r\n").unwrap_or(body);
+            let mut raw = body.strip_suffix(LINE_BREAKS).unwrap_or(body);
+            if options.trim == TrimPolicy::All {
+                raw = raw.trim();
+            }
+            let entry = Entry {
+                key,
+                value: normalized(raw, options),
+                line: 0,
+                span: 0..0,
+                raw,
+                quote: Some(Quote::Heredoc { expand }),
+            };
+            return (Ok(Some(entry)), next);
+        }
+        let at = &line[line.find("<<").unwrap_or_default()..];
+        return (Err(error(at, Reason::UnterminatedHeredoc)), rest);
+    }
+
+    let (key, assigned) = match split_assignment(line, options.dialect) {
+        Some((key, value)) => (key, value),
+        // compose passes a variable with just a key through from the environment
+        None if options.dialect == Dialect::Compose
+            && !trimmed.trim_end().contains(char::is_whitespace) =>
+        {
+            return (Ok(None), rest)
+        }
+        None => return (Err(error(trimmed, Reason::MissingEquals)), rest),
+    };
+    if unquote(key.trim()).is_empty() {
+        return (Err(error(key, Reason::EmptyKey)), rest);
+    }
+    let key = unquote(key.trim());
+
+    let value = assigned.trim_start();
+    if !value.starts_with(['\'', '"']) {
+        let comment = match options.comments.unwrap_or(options.dialect.comments()) {
+            CommentPolicy::Anywhere => value.find('#'),
+            CommentPolicy::AfterWhitespace => {
+                // the line up to the value, so a `#` right after the `=` isn't a comment
+                let start = value.as_ptr() as usize - line.as_ptr() as usize;
+                let before = |i| line[..start + i].ends_with(char::is_whitespace);
+                value
+                    .match_indices('#')
+                    .map(|(i, _)| i)
+                    .find(|&i| before(i))
+            }
+            CommentPolicy::Never => None,
+        };
+        let end = assigned.len() - value.len() + comment.unwrap_or(value.len());
+        let value = match options.trim {
+            TrimPolicy::Never => &assigned[..end],
+            _ => assigned[..end].trim(),
+        };
+        let entry = Entry {
+            key,
+            value: Cow::Borrowed(value),
+            line: 0,
+            span: 0..0,
+            raw: value,
+            quote: None,
+        };
+        return (Ok(Some(entry)), rest);
+    }
+
+    // quoted values can span lines, so the value is read from the input rather than the line
+    let start = value.as_ptr() as usize - input.as_ptr() as usize;
+    let (len, mut segments) = match scan(&input[start..]) {
+        Ok(scanned) => scanned,
+        Err(at) => {
+            return (
+                Err(error(&input[start + at..], Reason::UnterminatedQuote)),
+                rest,
+            )
+        }
+    };
+    let (written, tail) = input[start..].split_at(len);
+    let (trailing, rest) = split_line(tail);
+    let trailing = trailing.trim_start();
+    if !trailing.is_empty() && !trailing.starts_with('#') {
+        return (Err(error(trailing, Reason::TrailingCharacters)), rest);
+    }
+    if options.trim == TrimPolicy::All {
+        trim_segments(written, &mut segments);
+    }
+
+    let (raw, quote, value) = match (options.strip_quotes, &*segments) {
+        (false, _) => (written, None, normalized(written, options)),
+        (true, [(quote, range)]) => {
+            let raw = &written[range.clone()];
+            (raw, *quote, segment(raw, *quote, options))
+        }
+        (true, segments) => {
+            let value = (segments.iter())
+                .map(|(quote, range)| segment(&written[range.clone()], *quote, options))
+                .collect::<String>();
+            (written, Some(Quote::Joined), Cow::Owned(value))
+        }
+    };
+
+    let entry = Entry {
+        key,
+        value,
+        line: 0,
+        span: 0..0,
+        raw,
+        quote,
+    };
+    (Ok(Some(entry)), rest)
+}
+
+/// The key, delimiter and whether the lines are expanded, of the first line of a `KEY<<EOF` block
+///
+/// The lines of a block with a quoted delimiter, e.g. `KEY<<'EOF'`, are taken literally.
+fn heredoc(line: &str) -> Option<(&str, &str, bool)> {
+    let line = line.trim();
+    let line = (line.strip_prefix("export"))
+        .filter(|s| s.starts_with(char::is_whitespace))
+        .map_or(line, str::trim_start);
+    let (key, delimiter) = line.split_once("<<")?;
+    let key = key.trim_end();
+    let word = |s: &str| !s.is_empty() && !s.contains(|c: char| c.is_whitespace() || c == '=');
+    let quoted = unquote(delimiter.trim_start());
+    let expand = quoted.len() == delimiter.trim_start().len();
+    (word(key) && word(quoted)).then_some((key, quoted, expand))
+}
+
+/// Splits `KEY=value`, `export KEY=value` or fish's `set -x KEY value` into the key and value
+///
+/// [`Dialect::Ruby`] also splits `KEY: value`.
+fn split_assignment(line: &str, dialect: Dialect) -> Option<(&str, &str)> {
+    fn keyword<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+        line.strip_prefix(keyword)
+            .filter(|s| s.starts_with(char::is_whitespace))
+            .map(<str>::trim_start)
+    }
+
+    let line = line.trim_start();
+    if let Some(rest) = keyword(line, "set") {
+        let rest = keyword(rest, "-x").or_else(|| keyword(rest, "-gx"))?;
+        return rest.split_once(char::is_whitespace);
+    }
+
+    let line = keyword(line, "export").unwrap_or(line);
+    if dialect == Dialect::Ruby {
+        let key = line.split(['=', ':']).next().unwrap_or_default();
+        let word = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.');
+        if let Some(value) = line[key.len()..].strip_prefix(':') {
+            if !key.is_empty() && key.chars().all(word) && value.starts_with(char::is_whitespace) {
+                return Some((key, value));
+            }
+        }
+    }
+    line.split_once('=')
+}
+
+/// Where [`scan`] is in a value, with where the segment it's in started
+#[derive(Copy, Clone)]
+enum State {
+    Single(usize),
+    Double(usize),
+    /// Just after a `\` in double quotes
+    Escaped(usize),
+    /// After a closing quote
+    Closed,
+    /// In unquoted text that's joined to a quoted segment
+    Bare(usize),
+    /// Just after a `\` in unquoted text
+    BareEscaped(usize),
+}
+
+/// The quoted and unquoted segments of a value, by their quotes and byte ranges without them
+type Segments = Vec<(Option<Quote>, Range<usize>)>;
+
+/// Scans a value that starts with a quote, up to the whitespace after it
+///
+/// Segments that aren't separated by whitespace are one value, so `"a"'b'c` is `abc`, like in a shell.
+/// A quote only ends a segment if it's the same kind as the opening one and, in double quotes, isn't escaped.
+/// Outside of quotes a `\` escapes the next character, so `'it'\''s'` is `it's`, but not a line break.
+/// Produces the length of the value and its segments, or the offset of the quote that isn't closed.
+fn scan(input: &str) -> Result<(usize, Segments), usize> {
+    let (mut state, mut segments) = (State::Closed, Segments::new());
+    for (i, c) in input.char_indices() {
+        let ends_bare = matches!(c, '\'' | '"') || c.is_whitespace();
+        if let (State::Bare(start), true) = (state, ends_bare) {
+            segments.push((None, start..i));
+        }
+        state = match (state, c) {
+            (State::Single(start), '\'') => {
+                segments.push((Some(Quote::Single), start..i));
+                State::Closed
+            }
+            (State::Double(start), '"') => {
+                segments.push((Some(Quote::Double), start..i));
+                State::Closed
+            }
+            (State::Double(start), '\\') => State::Escaped(start),
+            (State::Escaped(start), _) => State::Double(start),
+            (State::Single(..) | State::Double(..), _) => state,
+            (State::Closed | State::Bare(..), '\'') => State::Single(i + 1),
+            (State::Closed | State::Bare(..), '"') => State::Double(i + 1),
+            (State::Closed | State::Bare(..), c) if c.is_whitespace() => return Ok((i, segments)),
+            (State::BareEscaped(start), c) if LINE_BREAKS.contains(&c) => {
+                segments.push((None, start..i));
+                return Ok((i, segments));
+            }
+            (State::Closed, '\\') => State::BareEscaped(i),
+            (State::Bare(start), '\\') => State::BareEscaped(start),
+            (State::BareEscaped(start), _) => State::Bare(start),
+            (State::Closed, _) => State::Bare(i),
+            (State::Bare(..), _) => state,
+        };
+    }
+
+    match state {
+        State::Single(start) | State::Double(start) | State::Escaped(start) => Err(start - 1),
+        State::Bare(start) | State::BareEscaped(start) => {
+            segments.push((None, start..input.len()));
+            Ok((input.len(), segments))
+        }
+        State::Closed => Ok((input.len(), segments)),
+    }
+}
+
+/// Removes the whitespace at the start of the first segment and at the end of the last one
+fn trim_segments(written: &str, segments: &mut Segments) {
+    if let Some((_, range)) = segments.first_mut() {
+        range.start = range.end - written[range.clone()].trim_start().len();
+    }
+    if let Some((_, range)) = segments.last_mut() {
+        range.end = range.start + written[range.clone()].trim_end().len();
+    }
+}
+
+/// The value of a segment of a quoted value, see [`scan`]
+///
+/// Escapes are interpreted in double quoted segments, and in unquoted ones where a `\` escapes any character.
+fn segment(text: &str, quote: Option<Quote>, options: Options) -> Cow<'_, str> {
+    match (quote, normalized(text, options)) {
+        (Some(Quote::Double), Cow::Borrowed(text)) => unescape(text),
+        (Some(Quote::Double), Cow::Owned(text)) => Cow::Owned(unescape(&text).into_owned()),
+        (None, text) if text.contains('\\') => {
+            let mut chars = text.chars();
+            let unescaped = core::iter::from_fn(|| match chars.next()? {
+                '\\' => Some(chars.next().unwrap_or('\\')),
+                c => Some(c),
+            });
+            Cow::Owned(unescaped.collect())
+        }
+        (_, text) => text,
+    }
+}
+
+/// `text`, with its line breaks replaced by `\n` if they're normalized
+fn normalized(text: &str, options: Options) -> Cow<'_, str> {
+    match options.normalize_newlines && has_line_breaks(text) {
+        true => Cow::Owned(normalize_newlines(text)),
+        false => Cow::Borrowed(text),
+    }
+}
+
+/// A [`Quote::Joined`] value as a template for [`expand`](crate::interpolate::expand) with escapes
+///
+/// Double quoted segments are kept as written, the literal `\` and `$` of the others are escaped:
+/// every one in single quoted segments, and the escaped ones in unquoted segments.
+pub fn template(written: &str, options: Options) -> String {
+    let (_, mut segments) = scan(written).expect("the value was parsed");
+    if options.trim == TrimPolicy::All {
+        trim_segments(written, &mut segments);
+    }
+    let mut out = String::with_capacity(written.len());
+    for (quote, range) in segments {
+        let text = normalized(&written[range], options);
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            let (c, literal) = match (quote, c) {
+                (Some(Quote::Double), c) => (c, false),
+                (Some(..), c) => (c, true),
+                (None, '\\') => (chars.next().unwrap_or('\\'), true),
+                (None, c) => (c, false),
+            };
+            if literal && matches!(c, '\\' | '$') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Interprets `\n`, `\t`, `\r`, `\\`, `\"`, `\$` and `\uXXXX`, anything else is kept verbatim
+fn unescape(input: &str) -> Cow<'_, str> {
+    if !input.contains('\\') {
+        return Cow::Borrowed(input);
+    }
+
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find('\\') {
+        out.push_str(&rest[..pos]);
+        rest = unescape_one(&rest[pos + 1..], &mut out);
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Interprets the escape at the start of `tail`, which follows a `\`, returning what's left
+pub fn unescape_one<'a>(tail: &'a str, out: &mut String) -> &'a str {
+    let mut iter = tail.chars();
+    match iter.next() {
+        Some('n') => out.push('\n'),
+        Some('t') => out.push('\t'),
+        Some('r') => out.push('\r'),
+        Some(c @ ('\\' | '"' | '$')) => out.push(c),
+        Some('u') => {
+            match tail
+                .get(1..5)
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .and_then(char::from_u32)
+            {
+                Some(c) => {
+                    out.push(c);
+                    return &tail[5..];
+                }
+                None => out.push_str("\\u"),
+            }
+        }
+        Some(c) => {
+            out.push('\\');
+            out.push(c);
+        }
+        None => out.push('\\'),
+    }
+    iter.as_str()
+}
+
+/// The key of an `unset KEY` or a bare `KEY` line, see `Loader::unset_directives`
+pub fn unset_key(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let line = line
+        .strip_prefix("unset")
+        .filter(|s| s.starts_with(char::is_whitespace))
+        .map_or(line, str::trim_start);
+    let (key, comment) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let comment = comment.trim_start();
+    let valid = !key.is_empty() && !key.contains(['=', '\'', '"', '#']);
+    (valid && (comment.is_empty() || comment.starts_with('#'))).then_some(key)
+}
+
+/// The key of an entry of `data` as it was written, with the quotes that were removed from it
+pub fn written_key<'a>(data: &'a str, key: &'a str) -> &'a str {
+    let start = (key.as_ptr() as usize).wrapping_sub(data.as_ptr() as usize);
+    let Some(end) = start
+        .checked_add(key.len())
+        .filter(|&end| end <= data.len())
+    else {
+        return key;
+    };
+    match (
+        data[..start].chars().next_back(),
+        data[end..].chars().next(),
+    ) {
+        (Some(q @ ('\'' | '"')), Some(c)) if q == c => &data[start - 1..end + 1],
+        _ => key,
+    }
+}
+
+fn unquote(input: &str) -> &str {
+    ['\'', '"']
+        .into_iter()
+        .find_map(|q| input.strip_prefix(q)?.strip_suffix(q))
+        .unwrap_or(input)
+}
+
+#[test]
+fn parse_octos_in_strings() {
+    macro_rules! val {
+        ($k:expr => $v:expr) => {
+            &[($k, $v)]
+        };
+    }
+
+    #[rustfmt::skip]
+    let tests: &[(&str, &[(&str, &str)])] = &[
+        (r##"FOO="#bar""##, val!("FOO"  => "#bar")),
+        (r"'asdf'='fdsa'",  val!("asdf" => "fdsa")),
+        (r##"#FOO="bar""##, &[]),
+    ];
+    for (input, expected) in tests {
+        let pairs = parse(input, Options::default()).collect::<Vec<_>>();
+        let pairs = pairs.iter().map(|p| (p.key, &*p.value)).collect::<Vec<_>>();
+        assert_eq!(pairs, *expected);
+    }
+}
+
+#[test]
+fn parse_with_options() {
+    let data = "FOO=abc#123\nBAR='baz' # comment";
+    let options = Options {
+        dialect: Dialect::Dotenv,
+        comments: Some(CommentPolicy::Never),
+        trim: TrimPolicy::Unquoted,
+        strip_quotes: false,
+        normalize_newlines: false,
+        percent_references: false,
+        case_insensitive: false,
+        max_value_length: None,
+    };
+    let pairs = parse(data, options).collect::<Vec<_>>();
+    assert_eq!(
+        pairs.iter().map(|p| (p.key, &*p.value)).collect::<Vec<_>>(),
+        [("FOO", "abc#123"), ("BAR", "'baz'")]
+    );
+}
+
+#[test]
+fn parse_quoted_segments() {
+    let data = r#"A="a"b"c"
+B='it'"'"'s'
+C="say \"hi\"" # a comment
+D="x" junk
+N=1
+E='$N'"-$N\n"-$N-\$N-\x
+"#;
+    let lines = parse_lines(data, Options::default()).collect::<Vec<_>>();
+    let values = lines.iter().flatten().map(|p| (p.key, &*p.value));
+    assert_eq!(
+        values.collect::<Vec<_>>(),
+        [
+            ("A", "abc"),
+            ("B", "it's"),
+            ("C", "say \"hi\""),
+            ("N", "1"),
+            ("E", "$N-$N\n-$N-$N-x"),
+        ]
+    );
+    let diagnostic = lines[3].as_ref().unwrap_err();
+    assert_eq!(
+        (diagnostic.reason, diagnostic.column),
+        (Reason::TrailingCharacters, 7)
+    );
+
+    let entries = lines.into_iter().flatten().collect::<Vec<_>>();
+    let resolved = crate::interpolate::resolve(&entries, &|_| None)
+        .pop()
+        .unwrap();
+    assert_eq!(resolved.unwrap().1, "$N-1\n-1-$N-x");
+
+    let options = Options {
+        strip_quotes: false,
+        ..Options::default()
+    };
+    let entry = parse("A=\"a\"'b' # c", options).next().unwrap();
+    assert_eq!(entry.value, "\"a\"'b'");
+}
+
+#[test]
+fn parse_escaped_quotes() {
+    let data = r#"DOUBLE="she said \"hi\""
+SINGLE='it'\''s'
+BARE='a'\ \"b\\
+EDGE='a'\
+NEXT=1"#;
+    let pairs = parse(data, Options::default()).collect::<Vec<_>>();
+    assert_eq!(
+        pairs.iter().map(|p| (p.key, &*p.value)).collect::<Vec<_>>(),
+        [
+            ("DOUBLE", "she said \"hi\""),
+            ("SINGLE", "it's"),
+            ("BARE", "a \"b\\"),
+            ("EDGE", "a\\"),
+            ("NEXT", "1"),
+        ]
+    );
+
+    let pairs = parse("A=1\nB='$A'\\$A\\'$A\"$A\"", Options::default()).collect::<Vec<_>>();
+    let resolved = crate::interpolate::resolve(&pairs, &|_| None)
+        .pop()
+        .unwrap();
+    assert_eq!(resolved.unwrap().1, "$A$A'11");
+}
+
+/// Every value of up to 5 of the characters that matter to the parser
+#[test]
+fn parse_every_value() {
+    const CHARS: [char; 7] = ['a', '"', '\'', '\\', ' ', '#', '\n'];
+    for len in 0..=5 {
+        for mut n in 0..CHARS.len().pow(len) {
+            let mut value = String::new();
+            for _ in 0..len {
+                value.push(CHARS[n % CHARS.len()]);
+                n /= CHARS.len();
+            }
+
+            // the chunks are the data, and a broken value never takes the next line with it
+            let data = format!("K={value}\nNEXT=1");
+            let text = chunks_in(&data, Options::default(), vec![]).map(|(chunk, _)| chunk);
+            assert_eq!(text.collect::<String>(), data);
+            let entries = parse(&data, Options::default()).collect::<Vec<_>>();
+            assert!(entries
+                .iter()
+                .all(|entry| data.get(entry.span.clone()).is_some()));
+            let last = entries.last().map(|entry| (entry.key, &*entry.value));
+            assert_eq!(last, Some(("NEXT", "1")), "{data:?}");
+
+            // single quoted segments joined by quoted or escaped single quotes, like a shell writes them
+            for quote in [r#"'"'"'"#, r"'\''"] {
+                let quoted = format!("K='{}'", value.replace('\'', quote));
+                let entry = parse(&quoted, Options::default()).next();
+                assert_eq!(entry.map(|entry| entry.value), Some(Cow::from(&*value)));
+            }
+        }
+    }
+}
+
+#[test]
+fn parse_comment_policies() {
+    let data = "A=abc#123\nB=abc #123\nC=#abc\nD= #abc\nE=a\t#b\nF=\"#a\" #b\nexport G=1#2";
+    let values = |comments| {
+        let options = Options {
+            comments: Some(comments),
+            ..Options::default()
+        };
+        let pairs = parse(data, options).map(|p| p.value.into_owned());
+        pairs.collect::<Vec<_>>()
+    };
+    assert_eq!(
+        values(CommentPolicy::Anywhere),
+        ["abc", "abc", "", "", "a", "#a", "1"]
+    );
+    assert_eq!(
+        values(CommentPolicy::AfterWhitespace),
+        ["abc#123", "abc", "#abc", "", "a", "#a", "1#2"]
+    );
+    assert_eq!(
+        values(CommentPolicy::Never),
+        ["abc#123", "abc #123", "#abc", "#abc", "a\t#b", "#a", "1#2"]
+    );
+}
+
+#[test]
+fn parse_trim_policies() {
+    let data = "A= a \nB=\"  b  \"\nC='  c  ' # c\nD= \" d \"'e '\nE=x # y\nF=\"\\n f\\t\"";
+    let values = |trim| {
+        let options = Options {
+            trim,
+            ..Options::default()
+        };
+        let entries = parse(data, options).collect::<Vec<_>>();
+        let resolved = crate::interpolate::resolve_with(&entries, None, options, &|_| None);
+        let values = entries.iter().map(|p| p.value.to_string());
+        assert!(resolved
+            .into_iter()
+            .map(Result::unwrap)
+            .map(|(_, v)| v)
+            .eq(values.clone()));
+        values.collect::<Vec<_>>()
+    };
+    assert_eq!(
+        values(TrimPolicy::Unquoted),
+        ["a", "  b  ", "  c  ", " d e ", "x", "\n f\t"]
+    );
+    assert_eq!(
+        values(TrimPolicy::All),
+        ["a", "b", "c", "d e", "x", "\n f\t"]
+    );
+    assert_eq!(
+        values(TrimPolicy::Never),
+        [" a ", "  b  ", "  c  ", " d e ", "x ", "\n f\t"]
+    );
+}
+
+#[test]
+fn parse_heredocs() {
+    let data = "A<<EOF\n{\"a\": \"$B\"}\n  x\nEOF\nexport B << 'END'\n$A\n\nEND\nC<<EOF\nEOF\nD=1<<2\nE<<EOF\nnever\nends";
+    let (entries, diagnostics): (Vec<_>, Vec<_>) =
+        parse_lines(data, Options::default()).partition(Result::is_ok);
+    let entries = entries.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+    let values = entries
+        .iter()
+        .map(|entry| (entry.key, entry.line, &*entry.value))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        [
+            ("A", 1, "{\"a\": \"$B\"}\n  x"),
+            ("B", 5, "$A\n"),
+            ("C", 9, ""),
+            ("D", 11, "1<<2"),
+        ]
+    );
+    let diagnostic = diagnostics.into_iter().map(Result::unwrap_err);
+    assert_eq!(
+        diagnostic.map(|d| (d.line, d.reason)).collect::<Vec<_>>(),
+        [
+            (12, Reason::UnterminatedHeredoc),
+            (13, Reason::MissingEquals),
+            (14, Reason::MissingEquals)
+        ]
+    );
+
+    let resolved = crate::interpolate::resolve_with(&entries, None, Options::default(), &|_| None);
+    let resolved = resolved.into_iter().map(|pair| pair.unwrap().1);
+    assert_eq!(
+        resolved.collect::<Vec<_>>(),
+        ["{\"a\": \"$A\n\"}\n  x", "$A\n", "", "1<<2"]
+    );
+
+    let chunks = chunks(data, Options::default()).map(|(chunk, _)| chunk);
+    assert_eq!(chunks.collect::<String>(), data);
+    assert!(is_open("A<<EOF\nline", Options::default()));
+}
+
+#[test]
+fn parse_multiline_values() {
+    let data = "KEY=\"-----BEGIN-----\nabc\r\n-----END-----\" # trailing\nNEXT = 'a\nb'\nBROKEN=\"unterminated\nAFTER=1";
+    let pairs = parse(data, Options::default()).collect::<Vec<_>>();
+    assert_eq!(
+        pairs.iter().map(|p| (p.key, &*p.value)).collect::<Vec<_>>(),
+        [
+            ("KEY", "-----BEGIN-----\nabc\r\n-----END-----"),
+            ("NEXT", "a\nb"),
+            ("AFTER", "1"),
+        ]
+    );
+}
+
+#[test]
+fn parse_line_numbers() {
+    let data = "# comment\n\nFOO=\"a\nb\"\r\nBAR=1\n";
+    let lines = parse(data, Options::default()).map(|p| (p.key, p.line));
+    assert_eq!(lines.collect::<Vec<_>>(), [("FOO", 3), ("BAR", 5)]);
+}
+
+#[test]
+fn parse_line_breaks() {
+    let data = "A=1\rB=2\u{2028}C=3\u{2029}\r\nD=\"x\r\ny\rz\\r\"\r\nE='p\rq'\rF=\"$A\r\n$B\"\r";
+    let entries = parse(data, Options::default()).collect::<Vec<_>>();
+    let lines = entries.iter().map(|p| (p.key, &*p.value, p.line));
+    assert_eq!(
+        lines.collect::<Vec<_>>(),
+        [
+            ("A", "1", 1),
+            ("B", "2", 2),
+            ("C", "3", 3),
+            ("D", "x\r\ny\rz\r", 5),
+            ("E", "p\rq", 8),
+            ("F", "$A\r\n$B", 10),
+        ]
+    );
+    assert_eq!(&data[entries[2].span.clone()], "C=3");
+
+    let options = Options {
+        normalize_newlines: true,
+        ..Options::default()
+    };
+    let entries = parse(data, options).collect::<Vec<_>>();
+    let values = entries.iter().map(|p| &*p.value);
+    assert_eq!(
+        values.collect::<Vec<_>>(),
+        ["1", "2", "3", "x\ny\nz\r", "p\nq", "$A\n$B"]
+    );
+    let resolved = crate::interpolate::resolve_with(&entries, None, options, &|_| None);
+    assert_eq!(resolved[5].as_ref().unwrap().1, "1\n2");
+}
+
+#[test]
+fn parse_with_diagnostics() {
+    let data = "GOOD=1\n  oops\n=value\nOPEN = 'never closed\nAFTER=2";
+    let lines = parse_lines(data, Options::default()).collect::<Vec<_>>();
+    let diagnostic = |line, column, reason| {
+        Err(Diagnostic {
+            line,
+            column,
+            reason,
+        })
+    };
+    assert_eq!(lines[1], diagnostic(2, 3, Reason::MissingEquals));
+    assert_eq!(lines[2], diagnostic(3, 1, Reason::EmptyKey));
+    assert_eq!(lines[3], diagnostic(4, 8, Reason::UnterminatedQuote));
+    assert_eq!(lines[4].as_ref().map(|e| e.key), Ok("AFTER"));
+}
+
+#[test]
+fn parse_escapes() {
+    let data = r#"
+    DOUBLE="line1\nline2\t\"quoted\" \\ \u00e9 \x"
+    SINGLE='line1\nline2'
+    "#;
+    let pairs = parse(data, Options::default()).collect::<Vec<_>>();
+    assert_eq!(
+        pairs.iter().map(|p| (p.key, &*p.value)).collect::<Vec<_>>(),
+        [
+            ("DOUBLE", "line1\nline2\t\"quoted\" \\ \u{e9} \\x"),
+            ("SINGLE", r"line1\nline2"),
+        ]
+    );
+}
+
+#[test]
+fn parse_export_prefix() {
+    let data = "export FOO=bar\nexport\tBAZ='qux'\nset -x FISH \"fish value\"\nset -gx GLOBAL 1\nexport=plain";
+    let pairs = parse(data, Options::default()).collect::<Vec<_>>();
+    assert_eq!(
+        pairs.iter().map(|p| (p.key, &*p.value)).collect::<Vec<_>>(),
+        [
+            ("FOO", "bar"),
+            ("BAZ", "qux"),
+            ("FISH", "fish value"),
+            ("GLOBAL", "1"),
+            ("export", "plain"),
+        ]
+    );
+
+    let envrc = "dotenv\nPATH_add bin\nexport DATABASE_URL=postgres://localhost/app";
+    let pairs = parse(envrc, Options::default()).collect::<Vec<_>>();
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].key, "DATABASE_URL");
+}
+
+#[test]
+fn parse_conditional_sections() {
+    let os = consts::OS;
+    let data = format!(
+        "A=1\n#if os={os}\nB=2\n#if family!={family}\nC=3\n#else\nC=4\n#endif\n#else\nB=5\n#endif\n#if os=none\nD=6",
+        family = consts::FAMILY
+    );
+    let pairs = parse(&data, Options::default()).collect::<Vec<_>>();
+    let pairs = pairs.iter().map(|p| (p.key, &*p.value)).collect::<Vec<_>>();
+    assert_eq!(pairs, [("A", "1"), ("B", "2"), ("C", "4")]);
+
+    let text = chunks(&data, Options::default())
+        .map(|(chunk, _)| chunk)
+        .collect::<String>();
+    assert_eq!(text, data);
+
+    let pairs = parse("#if you want\nA=1\n#endif", Options::default()).count();
+    assert_eq!(pairs, 1);
+}
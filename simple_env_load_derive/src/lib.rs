@@ -0,0 +1,180 @@
+/*!
+The derive macro for [`simple_env_load::EnvLoad`](https://docs.rs/simple_env_load/latest/simple_env_load/trait.EnvLoad.html),
+and the `load_env!` macro
+
+Use them through `simple_env_load` with the `derive` feature enabled, rather than depending on this crate directly.
+*/
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use simple_env_load_core::{interpolate, parse};
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+/// Derives `simple_env_load::EnvLoad` for a struct with named fields
+///
+/// Each field is read from the key of the same name in uppercase, and parsed with `FromStr`.
+///
+/// On the struct:
+/// - `#[env(prefix = "APP_")]` prepends a prefix to every key
+///
+/// On a field:
+/// - `#[env(rename = "KEY")]` reads the field from `KEY` instead, the prefix still applies
+/// - `#[env(default = "value")]` is parsed when the key is missing
+///
+/// Fields without a default are required, unless they are an `Option`.
+#[proc_macro_derive(EnvLoad, attributes(env))]
+pub fn derive_env_load(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(error(&input, "EnvLoad needs a struct with named fields")),
+        },
+        _ => return Err(error(&input, "EnvLoad can only be derived for structs")),
+    };
+
+    let mut prefix = String::new();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("env"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("prefix") {
+                return Err(meta.error("expected `prefix`"));
+            }
+            prefix = meta.value()?.parse::<LitStr>()?.value();
+            Ok(())
+        })?;
+    }
+
+    let mut inits = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields");
+        let mut key = ident.to_string().trim_start_matches("r#").to_uppercase();
+        let mut default = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("env"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    key = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("default") {
+                    default = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    return Err(meta.error("expected `rename` or `default`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let key = format!("{prefix}{key}");
+        let default = match default {
+            Some(default) => quote!(::core::option::Option::Some(#default)),
+            None => quote!(::core::option::Option::None),
+        };
+        let read = match is_option(&field.ty) {
+            true => quote!(optional),
+            false => quote!(required),
+        };
+        inits.push(quote! {
+            #ident: ::simple_env_load::__private::#read(&lookup, #key, #default)?
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::simple_env_load::EnvLoad for #name #ty_generics #where_clause {
+            fn from_lookup<F>(lookup: F) -> ::core::result::Result<Self, ::simple_env_load::EnvLoadError>
+            where
+                F: Fn(&str) -> ::core::option::Option<::std::string::String>,
+            {
+                ::core::result::Result::Ok(Self {
+                    #(#inits,)*
+                })
+            }
+        }
+    })
+}
+
+fn is_option(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(ty) if ty.qself.is_none() => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+fn error(input: &DeriveInput, msg: &str) -> syn::Error {
+    syn::Error::new(input.ident.span(), msg)
+}
+
+/// Parses an env file at compile time, expanding to a `&[(&str, &str)]` of its pairs in order
+///
+/// The path is relative to the crate's `Cargo.toml`, and the crate is rebuilt when the file changes.
+/// Nothing is read at runtime, so this works without a filesystem, e.g. for embedded or wasm targets.
+///
+/// The file is read with the same parser as `simple_env_load::parse_to_map`, so it produces the same pairs,
+/// except that references to keys that aren't in the file resolve to the environment of the build.
+/// A malformed line, or a reference that can't be expanded, is a compile error.
+///
+/// ```rust,ignore
+/// const ENV: &[(&str, &str)] = simple_env_load::load_env!(".env");
+/// ```
+#[proc_macro]
+pub fn load_env(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let path = syn::parse_macro_input!(input as LitStr);
+    expand_load_env(&path)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_load_env(path: &LitStr) -> syn::Result<TokenStream> {
+    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let file = std::path::Path::new(&dir).join(path.value());
+    let data = std::fs::read_to_string(&file).map_err(|err| {
+        syn::Error::new(
+            path.span(),
+            format!("cannot read '{}': {err}", file.display()),
+        )
+    })?;
+    let error = |err: &dyn std::fmt::Display| {
+        syn::Error::new(path.span(), format!("{}:{err}", file.display()))
+    };
+
+    let entries = parse::parse_lines(&data, parse::Options::default())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| error(&err))?;
+    let env = |key: &str| std::env::var(key).ok();
+    let (mut keys, mut values) = (Vec::<String>::new(), Vec::<String>::new());
+    for (entry, pair) in entries.iter().zip(interpolate::resolve(&entries, &env)) {
+        let (key, value) = pair.map_err(|err| error(&format_args!("{}: {err}", entry.line)))?;
+        // a key defined again keeps its place, with its last value
+        match keys.iter().position(|k| *k == key) {
+            Some(i) => values[i] = value,
+            None => {
+                keys.push(key);
+                values.push(value);
+            }
+        }
+    }
+
+    let file = file.to_string_lossy();
+    Ok(quote! {{
+        // tracks the file, so a change to it rebuilds the crate
+        const _: &[u8] = ::core::include_bytes!(#file);
+        &[#((#keys, #values)),*]
+    }})
+}
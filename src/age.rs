@@ -0,0 +1,58 @@
+use std::str::FromStr as _;
+
+/// Decrypts age encrypted `data` with the first of `identities` that can, see [`Loader::age_identity`](crate::Loader::age_identity)
+pub(crate) fn decrypt(identities: &str, data: &[u8]) -> Result<String, String> {
+    let identities = identities
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(::age::x25519::Identity::from_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("invalid age identity: {err}"))?;
+    if identities.is_empty() {
+        return Err("no age identities were given".into());
+    }
+
+    let mut error = None;
+    for identity in &identities {
+        match ::age::decrypt(identity, data) {
+            Ok(data) => return String::from_utf8(data).map_err(|err| err.to_string()),
+            Err(err) => error = Some(err.to_string()),
+        }
+    }
+    Err(error.unwrap_or_default())
+}
+
+#[test]
+fn decrypt_age_files() {
+    use ::age::secrecy::ExposeSecret as _;
+
+    let identity = ::age::x25519::Identity::generate();
+    let data = ::age::encrypt(&identity.to_public(), b"SIMPLE_ENV_LOAD_AGE=secret").unwrap();
+    let path = std::env::temp_dir().join("simple_env_load_age.env.enc");
+    std::fs::write(&path, data).unwrap();
+
+    let other = ::age::x25519::Identity::generate();
+    let identities = format!(
+        "# created: today\n{}\n{}\n",
+        other.to_string().expose_secret(),
+        identity.to_string().expose_secret()
+    );
+    let report = crate::Loader::new()
+        .path(&path)
+        .age_identity(identities)
+        .load()
+        .unwrap();
+    assert_eq!(
+        report.into_pairs(),
+        [("SIMPLE_ENV_LOAD_AGE".into(), "secret".into())]
+    );
+
+    let err = crate::Loader::new()
+        .path(&path)
+        .age_identity(other.to_string().expose_secret())
+        .load()
+        .unwrap_err();
+    assert!(matches!(err, crate::LoadError::Decrypt { .. }));
+    std::fs::remove_file(&path).unwrap();
+}
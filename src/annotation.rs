@@ -0,0 +1,297 @@
+use crate::{parse, Rule, Schema, Type};
+
+/// A key of an env file, with the annotations in the comments above it, see [`annotations`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnnotatedKey {
+    /// The name of the variable
+    pub key: String,
+    /// The 1-based line the key is defined on
+    pub line: usize,
+    /// Whether it was annotated with `@required`
+    pub required: bool,
+    /// The `@type`, e.g. `u16` or `duration`
+    pub ty: Option<String>,
+    /// The `@default` value
+    pub default: Option<String>,
+    /// The `@description`
+    pub description: Option<String>,
+}
+
+/// An annotation that couldn't be understood
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnnotationError {
+    /// The 1-based line of the annotation
+    pub line: usize,
+    /// What's wrong with it
+    pub reason: String,
+}
+
+impl std::fmt::Display for AnnotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for AnnotationError {}
+
+/// The keys of an env file, with the annotations in the comments directly above each of them
+///
+/// The annotations are `# @required`, `# @type: <type>`, `# @default: <value>` and `# @description: <text>`,
+/// one per comment line. A blank line ends the annotations for the next key, other comments don't.
+/// Types are `string`, `int`, `i8` to `i64`, `u8` to `u64`, `float`, `bool`, `url`, `duration` and `bytes`.
+/// See [`Schema::from_annotations`] to validate variables with them.
+///
+/// ```rust
+/// let data = "\
+/// ## @required
+/// ## @type: u16
+/// ## @default: 8080
+/// ## @description: the port to listen on
+/// PORT=
+///
+/// HOST=localhost
+/// ";
+/// let keys = simple_env_load::annotations(data).unwrap();
+///
+/// assert_eq!(keys[0].key, "PORT");
+/// assert!(keys[0].required);
+/// assert_eq!(keys[0].ty.as_deref(), Some("u16"));
+/// assert_eq!(keys[0].default.as_deref(), Some("8080"));
+/// assert_eq!(keys[0].description.as_deref(), Some("the port to listen on"));
+///
+/// assert_eq!(keys[1].key, "HOST");
+/// assert_eq!(keys[1].ty, None);
+/// ```
+pub fn annotations(data: &str) -> Result<Vec<AnnotatedKey>, AnnotationError> {
+    let (mut keys, mut pending, mut line) = (vec![], AnnotatedKey::default(), 1);
+    for (chunk, entry) in parse::chunks(data, Default::default()) {
+        let start = line;
+        line += parse::line_breaks(chunk);
+
+        match entry {
+            Ok(Some(entry)) => keys.push(AnnotatedKey {
+                key: entry.key.to_string(),
+                line: entry.line,
+                ..std::mem::take(&mut pending)
+            }),
+            Err(..) => pending = AnnotatedKey::default(),
+            Ok(None) => {
+                let comment = chunk.trim();
+                if comment.is_empty() {
+                    pending = AnnotatedKey::default();
+                    continue;
+                }
+                let Some(annotation) = comment.strip_prefix('#').map(str::trim) else {
+                    continue;
+                };
+                if let Some(annotation) = annotation.strip_prefix('@') {
+                    annotate(&mut pending, annotation).map_err(|reason| AnnotationError {
+                        line: start,
+                        reason,
+                    })?;
+                }
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Applies `name` or `name: value` to the annotations of a key
+fn annotate(key: &mut AnnotatedKey, annotation: &str) -> Result<(), String> {
+    let (name, value) = match annotation.split_once(':') {
+        Some((name, value)) => (name.trim(), Some(value.trim().to_string())),
+        None => (annotation.trim(), None),
+    };
+    let value = || value.clone().ok_or(format!("'@{name}' needs a value"));
+    match name {
+        "required" => key.required = true,
+        "type" => {
+            let ty = value()?;
+            rule(&ty)?;
+            key.ty = Some(ty)
+        }
+        "default" => key.default = Some(value()?),
+        "description" => key.description = Some(value()?),
+        name => return Err(format!("unknown annotation '@{name}'")),
+    }
+    Ok(())
+}
+
+/// The rule for an annotated type
+fn rule(ty: &str) -> Result<Rule, String> {
+    let int = |min, max| Rule::optional().ty(Type::Int).range(min, max);
+    Ok(match ty {
+        "string" | "str" => Rule::optional(),
+        "int" | "i64" => Rule::optional().ty(Type::Int),
+        "i8" => int(i8::MIN.into(), i8::MAX.into()),
+        "i16" => int(i16::MIN.into(), i16::MAX.into()),
+        "i32" => int(i32::MIN.into(), i32::MAX.into()),
+        "u8" => int(0, u8::MAX.into()),
+        "u16" => int(0, u16::MAX.into()),
+        "u32" => int(0, u32::MAX.into()),
+        "u64" => int(0, i64::MAX),
+        "float" | "f32" | "f64" => Rule::optional().ty(Type::Float),
+        "bool" => Rule::optional().ty(Type::Bool),
+        "url" => Rule::optional().ty(Type::Url),
+        "duration" => Rule::optional().ty(Type::Duration),
+        "bytes" => Rule::optional().ty(Type::Bytes),
+        ty => return Err(format!("unknown type '{ty}'")),
+    })
+}
+
+impl Schema {
+    /// A schema from the [`annotations`] of an env file, usually a `.env.example`
+    ///
+    /// A `@required` key must be set, unless it has a `@default`. Every key is declared, so keys without
+    /// annotations are optional strings.
+    ///
+    /// ```rust
+    /// let example = "# @type: bool\nDEBUG=\n# @required\nDATABASE_URL=";
+    /// let schema = simple_env_load::Schema::from_annotations(example).unwrap();
+    ///
+    /// let err = schema.validate(&[("DEBUG", "maybe")]).unwrap_err();
+    /// assert_eq!(err.violations().len(), 2);
+    /// ```
+    pub fn from_annotations(data: &str) -> Result<Self, AnnotationError> {
+        let mut schema = Self::new();
+        for key in annotations(data)? {
+            let mut rule = match &key.ty {
+                Some(ty) => rule(ty).expect("the type was checked"),
+                None => Rule::optional(),
+            };
+            rule.required = key.required && key.default.is_none();
+            schema = schema.key(key.key, rule);
+        }
+        Ok(schema)
+    }
+}
+
+/// The formats [`generate_docs`] can produce
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DocFormat {
+    /// A Markdown table
+    #[default]
+    Markdown,
+    /// A JSON array of objects with `key`, `type`, `required`, `default` and `description` fields
+    Json,
+}
+
+/// A reference of the variables in an annotated env file, see [`annotations`]
+///
+/// Keys without a `@type` are strings.
+///
+/// ```rust
+/// use simple_env_load::DocFormat;
+///
+/// let example = "# @type: u16\n# @default: 8080\n# @description: the port to listen on\nPORT=";
+///
+/// let docs = simple_env_load::generate_docs(example, DocFormat::Markdown).unwrap();
+/// assert_eq!(
+///     docs,
+///     "|Variable|Type|Required|Default|Description|\n\
+///     |---|---|---|---|---|\n\
+///     |`PORT`|u16|no|`8080`|the port to listen on|\n"
+/// );
+///
+/// let docs = simple_env_load::generate_docs(example, DocFormat::Json).unwrap();
+/// assert_eq!(
+///     docs,
+///     r#"[{"key":"PORT","type":"u16","required":false,"default":"8080","description":"the port to listen on"}]"#
+/// );
+/// ```
+pub fn generate_docs(data: &str, format: DocFormat) -> Result<String, AnnotationError> {
+    let keys = annotations(data)?;
+    Ok(match format {
+        DocFormat::Markdown => markdown(&keys),
+        DocFormat::Json => json(&keys),
+    })
+}
+
+fn markdown(keys: &[AnnotatedKey]) -> String {
+    let cell = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+    let mut out =
+        String::from("|Variable|Type|Required|Default|Description|\n|---|---|---|---|---|\n");
+    for key in keys {
+        let default = match &key.default {
+            Some(default) => format!("`{}`", cell(default)),
+            None => String::new(),
+        };
+        out.push_str(&format!(
+            "|`{}`|{}|{}|{}|{}|\n",
+            cell(&key.key),
+            cell(key.ty.as_deref().unwrap_or("string")),
+            if key.required { "yes" } else { "no" },
+            default,
+            cell(key.description.as_deref().unwrap_or_default()),
+        ));
+    }
+    out
+}
+
+fn json(keys: &[AnnotatedKey]) -> String {
+    let string = crate::write::json_string;
+    let optional = |text: &Option<String>| match text {
+        Some(text) => string(text),
+        None => "null".to_string(),
+    };
+
+    let keys = keys
+        .iter()
+        .map(|key| {
+            format!(
+                r#"{{"key":{},"type":{},"required":{},"default":{},"description":{}}}"#,
+                string(&key.key),
+                string(key.ty.as_deref().unwrap_or("string")),
+                key.required,
+                optional(&key.default),
+                optional(&key.description),
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", keys.join(","))
+}
+
+#[test]
+fn annotated_keys() {
+    let data = "\
+# @type: u8
+# just a comment
+A=1
+
+# @type: bool
+B=2
+# @required
+# @default: 3
+C=
+# @required
+D=
+";
+    let keys = annotations(data).unwrap();
+    let ty = keys.iter().map(|key| key.ty.as_deref()).collect::<Vec<_>>();
+    assert_eq!(ty, [Some("u8"), Some("bool"), None, None]);
+
+    let schema = Schema::from_annotations(data).unwrap();
+    let required = schema.keys().map(|(_, rule)| rule.is_required());
+    assert_eq!(required.collect::<Vec<_>>(), [false, false, false, true]);
+    let err = schema.validate(&[("A", "300"), ("D", "")]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value '300' for 'A': expected at most 255"
+    );
+
+    let err = annotations("A=1\n# @type: u128\nB=2").unwrap_err();
+    assert_eq!(err.to_string(), "2: unknown type 'u128'");
+    let err = annotations("# @requried\nA=1").unwrap_err();
+    assert_eq!(err.to_string(), "1: unknown annotation '@requried'");
+    assert!(annotations("# @default\nA=1").is_err());
+
+    let data = "# @description: a | b \"c\"\nA=";
+    let docs = generate_docs(data, DocFormat::Markdown).unwrap();
+    assert!(docs.ends_with("|`A`|string|no||a \\| b \"c\"|\n"));
+    let docs = generate_docs(data, DocFormat::Json).unwrap();
+    assert_eq!(
+        docs,
+        r#"[{"key":"A","type":"string","required":false,"default":null,"description":"a | b \"c\""}]"#
+    );
+}
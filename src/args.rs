@@ -0,0 +1,103 @@
+use clap::{Arg, ArgAction, Command};
+
+/// Use variables, e.g. from [`LoadReport::into_pairs`](crate::LoadReport::into_pairs), as the default values of `command`'s arguments
+///
+/// An argument's variable is the one it names with [`Arg::env`], or else its id in upper snake case,
+/// so `--log-level` is `LOG_LEVEL`. Arguments given on the command line, and variables set in the process
+/// environment for [`Arg::env`], still take precedence. A flag is set when its variable is `true`, `1`,
+/// `yes` or `on`, other values leave it alone.
+///
+/// ```rust
+/// use clap::{Arg, ArgAction, Command};
+///
+/// let command = Command::new("app")
+///     .arg(Arg::new("log-level").long("log-level"))
+///     .arg(Arg::new("port").long("port").env("CLAP_DOC_PORT"))
+///     .arg(Arg::new("verbose").long("verbose").action(ArgAction::SetTrue));
+///
+/// let vars = simple_env_load::parse_to_map("LOG_LEVEL=debug\nCLAP_DOC_PORT=80\nVERBOSE=yes");
+/// let command = simple_env_load::clap_defaults(command, vars);
+///
+/// let matches = command.get_matches_from(["app", "--log-level", "info"]);
+/// assert_eq!(matches.get_one::<String>("log-level").unwrap(), "info");
+/// assert_eq!(matches.get_one::<String>("port").unwrap(), "80");
+/// assert!(matches.get_flag("verbose"));
+/// ```
+pub fn clap_defaults<I, K, V>(command: Command, vars: I) -> Command
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: Into<String>,
+{
+    let vars = vars
+        .into_iter()
+        .map(|(key, value)| (key.as_ref().to_string(), value.into()))
+        .collect::<std::collections::HashMap<_, _>>();
+
+    command.mut_args(|arg| {
+        let key = match arg.get_env() {
+            Some(key) => key.to_string_lossy().into_owned(),
+            None => arg.get_id().as_str().to_ascii_uppercase().replace('-', "_"),
+        };
+        let Some(value) = vars.get(&key) else {
+            return arg;
+        };
+        default(arg, value)
+    })
+}
+
+fn default(arg: Arg, value: &str) -> Arg {
+    match arg.get_action() {
+        ArgAction::SetTrue => match crate::env::parse_bool(value) {
+            Ok(value) => arg.default_value(value.to_string()),
+            Err(..) => arg,
+        },
+        action if action.takes_values() => arg.default_value(value.to_string()),
+        _ => arg,
+    }
+}
+
+#[test]
+fn clap_default_values() {
+    let command = || {
+        Command::new("test")
+            .arg(Arg::new("name"))
+            .arg(
+                Arg::new("dry-run")
+                    .long("dry-run")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(Arg::new("count").short('c').action(ArgAction::Count))
+            .arg(
+                Arg::new("host")
+                    .long("host")
+                    .env("SIMPLE_ENV_LOAD_CLAP_HOST"),
+            )
+    };
+    let vars = [
+        ("NAME", "from-file"),
+        ("DRY_RUN", "maybe"),
+        ("COUNT", "3"),
+        ("SIMPLE_ENV_LOAD_CLAP_HOST", "file.example.com"),
+    ];
+
+    let matches = clap_defaults(command(), vars).get_matches_from(["test"]);
+    assert_eq!(matches.get_one::<String>("name").unwrap(), "from-file");
+    assert!(!matches.get_flag("dry-run"));
+    assert_eq!(matches.get_count("count"), 0);
+    assert_eq!(
+        matches.get_one::<String>("host").unwrap(),
+        "file.example.com"
+    );
+
+    let matches = clap_defaults(command(), vars).get_matches_from(["test", "given"]);
+    assert_eq!(matches.get_one::<String>("name").unwrap(), "given");
+
+    std::env::set_var("SIMPLE_ENV_LOAD_CLAP_HOST", "process.example.com");
+    let matches = clap_defaults(command(), vars).get_matches_from(["test"]);
+    std::env::remove_var("SIMPLE_ENV_LOAD_CLAP_HOST");
+    assert_eq!(
+        matches.get_one::<String>("host").unwrap(),
+        "process.example.com"
+    );
+}
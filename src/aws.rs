@@ -0,0 +1,194 @@
+use crate::{command, Source, SourceError};
+
+/// The parameters under a path in AWS SSM Parameter Store, see [`Loader::source`](crate::Loader::source)
+///
+/// They're read with the `aws` CLI, so its usual credentials and configuration apply. Secure strings are
+/// decrypted, and each key is the parameter's name without the path, e.g. `/app/prod/DB_URL` is `DB_URL`.
+/// Values are taken literally, they aren't expanded.
+///
+/// ```rust,no_run
+/// use simple_env_load::{Loader, SecretsManagerSecret, SsmParameters};
+///
+/// Loader::new()
+///     .path(".env")
+///     .source(SsmParameters::new("/app/prod").region("eu-west-1"))
+///     .source(SecretsManagerSecret::new("app/prod/db"))
+///     .load()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct SsmParameters {
+    path: String,
+    recursive: bool,
+    cli: Cli,
+}
+
+impl SsmParameters {
+    /// Read the parameters directly under `path`
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            recursive: false,
+            cli: Cli::default(),
+        }
+    }
+
+    /// Whether parameters further down the hierarchy are read too (default: `false`)
+    ///
+    /// The `/`s in the rest of their names become `_`s, e.g. `/app/prod/db/URL` is `db_URL` under `/app/prod`.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// The AWS region to use, instead of the configured one
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.cli.region = Some(region.into());
+        self
+    }
+
+    /// The AWS CLI profile to use, instead of the default one
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.cli.profile = Some(profile.into());
+        self
+    }
+}
+
+impl Source for SsmParameters {
+    fn read(&self) -> Result<String, SourceError> {
+        let mut args = vec!["ssm", "get-parameters-by-path", "--path", &self.path];
+        args.extend(["--with-decryption", "--output", "json"]);
+        if self.recursive {
+            args.push("--recursive");
+        }
+        let output = self.cli.run(args)?;
+        let pairs = parameters(&output, &self.path)?;
+        Ok(crate::to_env_string(pairs)?)
+    }
+
+    fn name(&self) -> String {
+        format!("ssm:{}", self.path)
+    }
+}
+
+/// A secret in AWS Secrets Manager, see [`Loader::source`](crate::Loader::source)
+///
+/// It's read with the `aws` CLI, like [`SsmParameters`]. A secret that is a JSON object, as made by the console's
+/// key/value editor, is a key for each field. Any other secret is parsed as env data.
+#[derive(Clone, Debug)]
+pub struct SecretsManagerSecret {
+    id: String,
+    cli: Cli,
+}
+
+impl SecretsManagerSecret {
+    /// Read the secret with this name or ARN
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            cli: Cli::default(),
+        }
+    }
+
+    /// The AWS region to use, instead of the configured one
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.cli.region = Some(region.into());
+        self
+    }
+
+    /// The AWS CLI profile to use, instead of the default one
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.cli.profile = Some(profile.into());
+        self
+    }
+}
+
+impl Source for SecretsManagerSecret {
+    fn read(&self) -> Result<String, SourceError> {
+        let args = [
+            "secretsmanager",
+            "get-secret-value",
+            "--secret-id",
+            &self.id,
+        ];
+        let args = args
+            .into_iter()
+            .chain(["--query", "SecretString", "--output", "text"]);
+        secret(self.cli.run(args)?)
+    }
+
+    fn name(&self) -> String {
+        format!("secretsmanager:{}", self.id)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Cli {
+    region: Option<String>,
+    profile: Option<String>,
+}
+
+impl Cli {
+    fn run<'a>(&'a self, args: impl IntoIterator<Item = &'a str>) -> Result<String, SourceError> {
+        let mut args = args.into_iter().collect::<Vec<_>>();
+        for (flag, value) in [("--region", &self.region), ("--profile", &self.profile)] {
+            if let Some(value) = value {
+                args.extend([flag, value]);
+            }
+        }
+        command::run("aws", &args).map_err(SourceError::new)
+    }
+}
+
+/// The (key, value) pairs in the output of `aws ssm get-parameters-by-path`
+fn parameters(output: &str, path: &str) -> Result<Vec<(String, String)>, SourceError> {
+    let output = serde_json::from_str::<serde_json::Value>(output).map_err(SourceError::new)?;
+    let parameters = output["Parameters"]
+        .as_array()
+        .ok_or_else(|| SourceError::new("expected a list of parameters"))?;
+
+    let prefix = path.trim_end_matches('/');
+    let mut pairs = vec![];
+    for parameter in parameters {
+        let (name, value) = match (parameter["Name"].as_str(), parameter["Value"].as_str()) {
+            (Some(name), Some(value)) => (name, value),
+            _ => return Err(SourceError::new("expected a parameter name and value")),
+        };
+        let key = name.strip_prefix(prefix).unwrap_or(name);
+        pairs.push((
+            key.trim_start_matches('/').replace('/', "_"),
+            value.to_string(),
+        ));
+    }
+    Ok(pairs)
+}
+
+/// A secret string as env data
+fn secret(data: String) -> Result<String, SourceError> {
+    let fields = match serde_json::from_str(&data) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => return Ok(data),
+    };
+    crate::source::json_fields(fields)
+}
+
+#[test]
+fn aws_output() {
+    let output = r#"{"Parameters": [
+        {"Name": "/app/prod/DB_URL", "Type": "SecureString", "Value": "postgres://db/$app"},
+        {"Name": "/app/prod/cache/TTL", "Type": "String", "Value": "60"}
+    ]}"#;
+    let data = crate::to_env_string(parameters(output, "/app/prod/").unwrap()).unwrap();
+    assert_eq!(
+        crate::parse_to_map(&data),
+        [
+            ("DB_URL".into(), "postgres://db/$app".into()),
+            ("cache_TTL".into(), "60".into())
+        ]
+    );
+    assert!(parameters("not json", "/app").is_err());
+
+    let data = secret(r#"{"USER": "app", "PORT": 5432, "DEBUG": false}"#.into()).unwrap();
+    assert_eq!(data, "DEBUG=false\nPORT=5432\nUSER=app\n");
+    assert_eq!(secret("USER=app".into()).unwrap(), "USER=app");
+}
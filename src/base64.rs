@@ -0,0 +1,63 @@
+/// Decodes standard or URL safe base64, padding is optional
+pub(crate) fn decode(input: &str) -> Result<Vec<u8>, String> {
+    let input = input.trim_end_matches('=');
+    if input.len() % 4 == 1 {
+        return Err("invalid base64 length".into());
+    }
+
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let (mut bits, mut len) = (0u32, 0);
+    for c in input.chars() {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            c => return Err(format!("invalid base64 character '{c}'")),
+        };
+        bits = bits << 6 | value;
+        len += 6;
+        if len >= 8 {
+            len -= 8;
+            out.push((bits >> len) as u8);
+        }
+    }
+    Ok(out)
+}
+
+/// Encodes standard base64, with padding
+pub(crate) fn encode(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | (byte as u32) << (16 - i * 8)
+        });
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(ALPHABET[(bits >> (18 - i * 6) & 63) as usize] as char),
+                false => out.push('='),
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn base64_round_trip() {
+    assert_eq!(decode("aGVsbG8gd29ybGQ=").unwrap(), b"hello world");
+    assert_eq!(decode("aGVsbG8gd29ybGQ").unwrap(), b"hello world");
+    assert_eq!(decode("-_8").unwrap(), [0xfb, 0xff]);
+    assert_eq!(decode("+/8=").unwrap(), [0xfb, 0xff]);
+    assert_eq!(decode("").unwrap(), b"");
+    assert!(decode("abcde").is_err());
+    assert!(decode("ab$=").is_err());
+
+    for input in ["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+        assert_eq!(decode(&encode(input.as_bytes())).unwrap(), input.as_bytes());
+    }
+    assert_eq!(encode(b"foob"), "Zm9vYg==");
+    assert_eq!(encode(&[0xfb, 0xff]), "+/8=");
+}
@@ -0,0 +1,340 @@
+//! `senv`, loads env files with the same rules as the library
+
+use std::process::ExitCode;
+
+use simple_env_load::{Backend, DocFormat, LintKind, Loader, Shell};
+
+const USAGE: &str = "\
+usage: senv run [options] -- <command> [args...]
+       senv export [-s <shell>] [options]
+       senv check [-e <example>] [--secrets] [files...]
+       senv diff [--mask] <old> <new>
+       senv docs [--json] [file]
+
+run loads the env files, then runs the command with them in its environment
+
+run options:
+    -f, --file <path>       an env file to load, - is stdin, can be repeated (default: .env, if it exists)
+    -p, --profile <name>    load .env, .env.local, .env.<name> and .env.<name>.local if they exist
+        --no-override       don't replace variables that are already set
+    -h, --help              print this message
+
+export prints a script that sets the variables of the env files, e.g. eval \"$(senv export)\"
+
+export options:
+    -s, --shell <shell>     sh, fish, powershell or direnv (default: sh)
+    as well as the file options of run
+
+check reports problems in the env files (default: .env, - is stdin), exiting with 1 if there are any
+
+check options:
+    -e, --example <path>    also report keys missing from, or not documented in, an example file
+        --secrets           also report values that look like credentials, such as tokens or private keys
+
+diff prints the keys that were removed (-), added (+) or changed (~) between two env files, exiting with 1 if there are any
+
+diff options:
+        --mask              hide the values
+
+docs prints a reference of the variables in an annotated env file (default: .env.example)
+
+docs options:
+        --json              print JSON instead of a Markdown table";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.split_first() {
+        Some((cmd, args)) if cmd == "run" => run(args),
+        Some((cmd, args)) if cmd == "check" => check(args),
+        Some((cmd, args)) if cmd == "export" => export(args),
+        Some((cmd, args)) if cmd == "diff" => diff(args),
+        Some((cmd, args)) if cmd == "docs" => docs(args),
+        Some((cmd, _)) if cmd == "-h" || cmd == "--help" => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Some((cmd, _)) => Err(format!("unknown subcommand '{cmd}'")),
+        None => Err("a subcommand is required".into()),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("senv: {err}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<ExitCode, String> {
+    let (options, command) = match args.iter().position(|arg| arg == "--") {
+        Some(pos) => (&args[..pos], &args[pos + 1..]),
+        None => (args, &[][..]),
+    };
+
+    let (mut options, mut files) = (options.iter(), Files::new());
+    while let Some(option) = options.next() {
+        if files.option(option, &mut options)? {
+            continue;
+        }
+        match option.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            option => return Err(format!("unknown option '{option}'")),
+        }
+    }
+
+    let (program, args) = command.split_first().ok_or("a command is required")?;
+    let mut command = match files.into_loader().command(program) {
+        Ok(command) => command,
+        Err(err) => return Ok(fail(err)),
+    };
+    command.args(args);
+    Ok(exec(command).unwrap_or_else(|err| fail(format!("cannot run '{program}': {err}"))))
+}
+
+/// The env files to load, from the options `run` and `export` share
+struct Files {
+    loader: Loader,
+    files: usize,
+}
+
+impl Files {
+    fn new() -> Self {
+        Self {
+            loader: Loader::new(),
+            files: 0,
+        }
+    }
+
+    /// Applies `option`, taking its value from `options`, producing whether it was one of the shared options
+    fn option(
+        &mut self,
+        option: &str,
+        options: &mut std::slice::Iter<String>,
+    ) -> Result<bool, String> {
+        let mut value = || {
+            options
+                .next()
+                .ok_or_else(|| format!("'{option}' needs a value"))
+        };
+        let loader = std::mem::take(&mut self.loader);
+        self.loader = match option {
+            "-f" | "--file" => {
+                self.files += 1;
+                loader.path(value()?)
+            }
+            "-p" | "--profile" => {
+                self.files += 1;
+                loader.profile(value()?)
+            }
+            "--no-override" => loader.override_existing(false),
+            _ => {
+                self.loader = loader;
+                return Ok(false);
+            }
+        };
+        Ok(true)
+    }
+
+    fn into_loader(self) -> Loader {
+        match self.files {
+            0 => self.loader.optional_path(".env"),
+            _ => self.loader,
+        }
+    }
+}
+
+/// Errors that aren't about the usage don't print it
+fn fail(err: impl std::fmt::Display) -> ExitCode {
+    eprintln!("senv: {err}");
+    ExitCode::FAILURE
+}
+
+fn export(args: &[String]) -> Result<ExitCode, String> {
+    let (mut options, mut files, mut shell) = (args.iter(), Files::new(), Shell::Posix);
+    while let Some(option) = options.next() {
+        if files.option(option, &mut options)? {
+            continue;
+        }
+        match option.as_str() {
+            "-s" | "--shell" => {
+                shell = match options.next().map(String::as_str) {
+                    Some("sh" | "bash" | "zsh" | "posix") => Shell::Posix,
+                    Some("fish") => Shell::Fish,
+                    Some("powershell" | "pwsh") => Shell::PowerShell,
+                    Some("direnv") => Shell::Direnv,
+                    Some(shell) => return Err(format!("unknown shell '{shell}'")),
+                    None => return Err(format!("'{option}' needs a value")),
+                }
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            option => return Err(format!("unknown option '{option}'")),
+        }
+    }
+
+    // the store keeps the variables away from this process, they're only printed
+    let loader = files.into_loader().backend(Backend::Store);
+    let script = loader
+        .load()
+        .map_err(|err| err.to_string())
+        .and_then(|report| {
+            simple_env_load::to_shell_string(shell, report.into_pairs())
+                .map_err(|err| err.to_string())
+        });
+    match script {
+        Ok(script) => print!("{script}"),
+        Err(err) => return Ok(fail(err)),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn check(args: &[String]) -> Result<ExitCode, String> {
+    let (mut args, mut files, mut example, mut secrets) = (args.iter(), vec![], None, false);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-e" | "--example" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| format!("'{arg}' needs a value"))?;
+                example = Some(path)
+            }
+            "--secrets" => secrets = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            option if option.starts_with('-') && option != "-" => {
+                return Err(format!("unknown option '{option}'"))
+            }
+            path => files.push(path),
+        }
+    }
+    if files.is_empty() {
+        files.push(".env");
+    }
+
+    let example = match example.map(|path| read(path).map(|data| (path, data))) {
+        Some(Ok(example)) => Some(example),
+        Some(Err(err)) => return Ok(fail(err)),
+        None => None,
+    };
+
+    let mut found = false;
+    for path in files {
+        let data = match read(path) {
+            Ok(data) => data,
+            Err(err) => return Ok(fail(err)),
+        };
+        let mut lints = match &example {
+            Some((_, example)) => simple_env_load::lint_with_example(&data, example),
+            None => simple_env_load::lint(&data),
+        };
+        if secrets {
+            lints.extend(simple_env_load::lint_secrets(&data));
+        }
+        for lint in &lints {
+            match (&lint.kind, &example) {
+                (LintKind::Missing { .. }, Some((example, _))) => {
+                    println!("{path}: {example}:{lint}")
+                }
+                _ => println!("{path}:{lint}"),
+            }
+        }
+        found |= !lints.is_empty();
+    }
+
+    Ok(match found {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    })
+}
+
+fn diff(args: &[String]) -> Result<ExitCode, String> {
+    let (mut mask, mut files) = (false, vec![]);
+    for arg in args {
+        match arg.as_str() {
+            "--mask" => mask = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            option if option.starts_with('-') && option != "-" => {
+                return Err(format!("unknown option '{option}'"))
+            }
+            path => files.push(path),
+        }
+    }
+    let [old, new] = files[..] else {
+        return Err("two files are required".into());
+    };
+
+    let (old, new) = match read(old).and_then(|old| Ok((old, read(new)?))) {
+        Ok(data) => data,
+        Err(err) => return Ok(fail(err)),
+    };
+    let diff = match mask {
+        true => simple_env_load::diff(&old, &new).masked(),
+        false => simple_env_load::diff(&old, &new),
+    };
+    if diff.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+    println!("{diff}");
+    Ok(ExitCode::FAILURE)
+}
+
+/// Reads a file, or stdin for `-`
+fn read(path: &str) -> Result<String, String> {
+    let data = match path {
+        "-" => std::io::read_to_string(std::io::stdin()),
+        path => std::fs::read_to_string(path),
+    };
+    data.map_err(|err| format!("cannot read '{path}': {err}"))
+}
+
+fn docs(args: &[String]) -> Result<ExitCode, String> {
+    let (mut format, mut file) = (DocFormat::Markdown, None);
+    for arg in args {
+        match arg.as_str() {
+            "--json" => format = DocFormat::Json,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            option if option.starts_with('-') => return Err(format!("unknown option '{option}'")),
+            path if file.is_none() => file = Some(path),
+            _ => return Err("only one file can be documented".into()),
+        }
+    }
+
+    let path = file.unwrap_or(".env.example");
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) => return Ok(fail(format!("cannot read '{path}': {err}"))),
+    };
+    match simple_env_load::generate_docs(&data, format) {
+        Ok(docs) => println!("{}", docs.trim_end()),
+        Err(err) => return Ok(fail(format!("{path}:{err}"))),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Replaces this process with the command, so signals and the exit status are the child's
+#[cfg(unix)]
+fn exec(mut command: std::process::Command) -> std::io::Result<ExitCode> {
+    use std::os::unix::process::CommandExt as _;
+    Err(command.exec())
+}
+
+/// Runs the command, exiting with its exit status
+#[cfg(not(unix))]
+fn exec(mut command: std::process::Command) -> std::io::Result<ExitCode> {
+    let status = command.status()?;
+    Ok(ExitCode::from(status.code().unwrap_or(1) as u8))
+}
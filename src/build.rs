@@ -0,0 +1,89 @@
+//! Loading env files in a build script, so `env!` sees their variables at compile time
+//!
+//! With `simple_env_load` in `[build-dependencies]`, calling this from the `main` of `build.rs`
+//! ```rust,no_run
+//! simple_env_load::build::emit([".env"]).unwrap();
+//! ```
+//! lets the crate use `env!("API_URL")` when `.env` has `API_URL=...`.
+
+use std::{fmt::Write as _, path::Path};
+
+use crate::{LoadError, Loader};
+
+/// Parse the files, then print the `cargo:rustc-env=KEY=VALUE` and `cargo:rerun-if-changed=PATH` lines for them
+///
+/// The files are required, see [`emit_loader`] to configure how they're loaded.
+pub fn emit<I, T>(paths: I) -> Result<(), LoadError>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<Path>,
+{
+    emit_loader(&Loader::new().paths(paths))
+}
+
+/// Print the `cargo:rustc-env=KEY=VALUE` and `cargo:rerun-if-changed=PATH` lines for the files of `loader`
+///
+/// The build script's environment isn't changed. Only the files that exist are watched for changes,
+/// otherwise Cargo would run the build script on every build. A value with a line break can't be passed
+/// to the compiler, it's a [`LoadError::Value`].
+pub fn emit_loader(loader: &Loader) -> Result<(), LoadError> {
+    print!("{}", lines(loader)?);
+    Ok(())
+}
+
+fn lines(loader: &Loader) -> Result<String, LoadError> {
+    let mut out = String::new();
+    let report = loader.evaluate_all()?;
+
+    let mut files = loader.files()?;
+    files.extend(report.vars().iter().map(|var| var.origin.path.clone()));
+    let mut seen = std::collections::HashSet::new();
+    for file in files
+        .iter()
+        .filter(|file| file.is_file() && seen.insert(*file))
+    {
+        writeln!(out, "cargo:rerun-if-changed={}", file.display()).expect("writing to a string");
+    }
+
+    for var in report.vars() {
+        if var.value.contains(['\n', '\r']) {
+            return Err(LoadError::Value {
+                key: var.key.clone(),
+                origin: var.origin.clone(),
+                reason: "a value with a line break can't be passed to the compiler".into(),
+            });
+        }
+        writeln!(out, "cargo:rustc-env={}={}", var.key, var.value).expect("writing to a string");
+    }
+    Ok(out)
+}
+
+#[test]
+fn emit_cargo_lines() {
+    let dir = std::env::temp_dir();
+    let path = dir.join("simple_env_load_build.env");
+    std::fs::write(
+        &path,
+        "SIMPLE_ENV_LOAD_BUILD_A=1\nSIMPLE_ENV_LOAD_BUILD_B=\"a b\"",
+    )
+    .unwrap();
+
+    let loader = Loader::new()
+        .path(&path)
+        .optional_path(dir.join("simple_env_load_build_missing.env"));
+    assert_eq!(
+        lines(&loader).unwrap(),
+        format!(
+            "cargo:rerun-if-changed={}\n\
+            cargo:rustc-env=SIMPLE_ENV_LOAD_BUILD_A=1\n\
+            cargo:rustc-env=SIMPLE_ENV_LOAD_BUILD_B=a b\n",
+            path.display()
+        )
+    );
+    assert!(std::env::var("SIMPLE_ENV_LOAD_BUILD_A").is_err());
+
+    std::fs::write(&path, "SIMPLE_ENV_LOAD_BUILD_A=\"1\n2\"").unwrap();
+    let err = lines(&loader).unwrap_err();
+    assert!(matches!(err, LoadError::Value { key, .. } if key == "SIMPLE_ENV_LOAD_BUILD_A"));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,74 @@
+use std::process::Command;
+
+/// Runs a command line if its program is one of `allowed`, producing its output without the trailing newlines
+///
+/// The line is split into words like a shell would, honoring quotes, but no shell is involved.
+pub(crate) fn run_allowed(line: &str, allowed: &[String]) -> Result<String, String> {
+    let words = split_words(line)?;
+    let (program, args) = words.split_first().ok_or("the command is empty")?;
+    if !allowed.iter().any(|p| p == program) {
+        return Err(format!("'{program}' is not an allowed command"));
+    }
+    run(program, args)
+}
+
+/// Runs a program, producing its output without the trailing newlines
+pub(crate) fn run(program: &str, args: &[impl AsRef<std::ffi::OsStr>]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} {}", output.status, stderr.trim())
+            .trim()
+            .to_string());
+    }
+
+    let stdout = String::from_utf8(output.stdout).map_err(|err| err.to_string())?;
+    Ok(stdout.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Splits a command line on whitespace, single and double quotes group words together
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let (mut words, mut word, mut quote, mut in_word) = (vec![], String::new(), None, false);
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, c) if c.is_whitespace() => {
+                if std::mem::take(&mut in_word) {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            (None, '\'' | '"') => (quote, in_word) = (Some(c), true),
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                word.extend(chars.next());
+                in_word = true;
+            }
+            (_, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err("the command has an unterminated quote".into());
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+#[test]
+fn split_command_words() {
+    assert_eq!(
+        split_words(r#"git  log -1 --format='%h %s' "a b"\ c"#).unwrap(),
+        ["git", "log", "-1", "--format=%h %s", "a b c"]
+    );
+    assert_eq!(split_words("echo ''").unwrap(), ["echo", ""]);
+    assert!(split_words("echo 'oops").is_err());
+    assert!(run_allowed("rm -rf /", &["echo".into()]).is_err());
+}
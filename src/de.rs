@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer as _, Visitor};
+
+use crate::Tree;
+
+/// Deserialize an env string into `T`
+///
+/// Values are parsed and expanded like [`parse_to_map`](crate::parse_to_map), then matched to fields by their key:
+/// - keys are lowercased, so `DATABASE_URL` fills the field `database_url`
+/// - a `__` in a key separates nested structs (or maps), so `DB__PORT` fills `db.port`
+/// - an `Option` is `None` when its key is missing or empty
+/// - sequences are comma separated, e.g. `HOSTS=a,b,c`
+/// - booleans can also be `1`/`0`, `yes`/`no` or `on`/`off`
+///
+/// Anything else, like renaming fields or defaults, is done with the usual `serde` attributes.
+///
+/// ```rust
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     name: String,
+///     #[serde(rename = "log")]
+///     log_level: Option<String>,
+///     hosts: Vec<String>,
+///     db: Database,
+/// }
+///
+/// #[derive(serde::Deserialize)]
+/// struct Database {
+///     port: u16,
+///     debug: bool,
+/// }
+///
+/// let data = "NAME=app\nHOSTS=a.example.com, b.example.com\nDB__PORT=5432\nDB__DEBUG=yes";
+/// let config: Config = simple_env_load::from_str(data).unwrap();
+/// assert_eq!(config.name, "app");
+/// assert_eq!(config.log_level, None);
+/// assert_eq!(config.hosts, ["a.example.com", "b.example.com"]);
+/// assert_eq!((config.db.port, config.db.debug), (5432, true));
+/// ```
+pub fn from_str<T: DeserializeOwned>(data: &str) -> Result<T, DeserializeError> {
+    let mut root = Tree::default();
+    for (key, value) in crate::parse_to_map(data) {
+        let node = key.split("__").fold(&mut root, |node, part| {
+            node.children.entry(part.to_ascii_lowercase()).or_default()
+        });
+        node.value = Some(value);
+    }
+
+    T::deserialize(NodeDeserializer {
+        node: &root,
+        key: String::new(),
+    })
+}
+
+/// An error produced by [`from_str`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeserializeError(String);
+
+impl std::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl de::Error for DeserializeError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+struct NodeDeserializer<'a> {
+    node: &'a Tree,
+    key: String,
+}
+
+impl NodeDeserializer<'_> {
+    fn value(&self) -> Result<&str, DeserializeError> {
+        self.node
+            .value
+            .as_deref()
+            .ok_or_else(|| DeserializeError(format!("expected a value for '{}'", self.key)))
+    }
+
+    fn parse<T>(&self) -> Result<T, DeserializeError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let value = self.value()?;
+        value.trim().parse().map_err(|err| self.invalid(value, err))
+    }
+
+    fn invalid(&self, value: &str, err: impl std::fmt::Display) -> DeserializeError {
+        DeserializeError(format!("invalid value '{value}' for '{}': {err}", self.key))
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            visitor.$visit(self.parse()?)
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for NodeDeserializer<'_> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match (&self.node.value, self.node.children.is_empty()) {
+            (Some(value), true) => visitor.visit_str(value),
+            _ => self.deserialize_map(visitor),
+        }
+    }
+
+    parse_value! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let value = self.value()?;
+        let value = crate::env::parse_bool(value).map_err(|err| self.invalid(value, err))?;
+        visitor.visit_bool(value)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.value()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_bytes(self.value()?.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.node.value.as_deref() {
+            Some("") | None if self.node.children.is_empty() => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let value = self.value()?.trim();
+        let key = &self.key;
+        let nodes = value
+            .split(',')
+            .filter(|_| !value.is_empty())
+            .map(|item| Tree {
+                value: Some(item.trim().to_string()),
+                children: BTreeMap::new(),
+            })
+            .collect::<Vec<_>>();
+        visitor.visit_seq(de::value::SeqDeserializer::new(nodes.iter().map(|node| {
+            NodeDeserializer {
+                node,
+                key: key.clone(),
+            }
+        })))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let key = &self.key;
+        visitor.visit_map(de::value::MapDeserializer::new(
+            self.node.children.iter().map(|(name, node)| {
+                let key = match key.is_empty() {
+                    true => name.to_ascii_uppercase(),
+                    false => format!("{key}__{}", name.to_ascii_uppercase()),
+                };
+                (name.as_str(), NodeDeserializer { node, key })
+            }),
+        ))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.value()?.trim().into_deserializer())
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+impl<'de> de::IntoDeserializer<'de, DeserializeError> for NodeDeserializer<'_> {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+#[test]
+fn deserialize_nested() {
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Debug,
+        Release,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        mode: Mode,
+        retries: Option<u8>,
+        timeout: Option<f32>,
+        ports: Vec<u16>,
+        #[serde(default)]
+        tags: Vec<String>,
+        limits: BTreeMap<String, u32>,
+    }
+
+    let data =
+        "MODE=release\nRETRIES=\nTIMEOUT=1.5\nPORTS=80,443\nLIMITS__CPU=2\nLIMITS__MEMORY=512";
+    let config: Config = from_str(data).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            mode: Mode::Release,
+            retries: None,
+            timeout: Some(1.5),
+            ports: vec![80, 443],
+            tags: vec![],
+            limits: [("cpu".into(), 2), ("memory".into(), 512)].into(),
+        }
+    );
+
+    let err = from_str::<Config>("MODE=debug\nPORTS=80,http\nLIMITS__CPU=1").unwrap_err();
+    assert!(err.to_string().contains("'http' for 'PORTS'"), "{err}");
+
+    let err = from_str::<Config>("PORTS=80").unwrap_err();
+    assert_eq!(err.to_string(), "missing field `mode`");
+}
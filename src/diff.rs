@@ -0,0 +1,136 @@
+/// A key whose value differs, see [`EnvDiff`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangedValue {
+    /// The name of the variable
+    pub key: String,
+    /// Its value in the first env data
+    pub old: String,
+    /// Its value in the second env data
+    pub new: String,
+}
+
+/// The keys that differ between two env files, see [`diff`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvDiff {
+    /// The `(key, value)` pairs that are only in the second, in its order
+    pub added: Vec<(String, String)>,
+    /// The `(key, value)` pairs that are only in the first, in its order
+    pub removed: Vec<(String, String)>,
+    /// The keys whose values differ, in the order of the first
+    pub changed: Vec<ChangedValue>,
+}
+
+impl EnvDiff {
+    /// The value [`masked`](Self::masked) replaces values with
+    pub const MASK: &'static str = "****";
+
+    /// Whether both define the same keys, with the same values
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Replaces every value with [`EnvDiff::MASK`], so the diff can be shared without leaking secrets
+    ///
+    /// Empty values are kept, so a value that was cleared can still be told apart.
+    pub fn masked(mut self) -> Self {
+        let mask = |value: &mut String| {
+            if !value.is_empty() {
+                *value = Self::MASK.to_string()
+            }
+        };
+        let pairs = self.added.iter_mut().chain(&mut self.removed);
+        pairs.for_each(|(_, value)| mask(value));
+        for change in &mut self.changed {
+            mask(&mut change.old);
+            mask(&mut change.new);
+        }
+        self
+    }
+}
+
+/// One line per key, `+ KEY=value` for added keys, `- KEY=value` for removed ones and `~ KEY: old -> new` for changed ones
+impl std::fmt::Display for EnvDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = (self
+            .removed
+            .iter()
+            .map(|(key, value)| format!("- {key}={value}")))
+        .chain(
+            self.added
+                .iter()
+                .map(|(key, value)| format!("+ {key}={value}")),
+        )
+        .chain(
+            (self.changed.iter())
+                .map(|change| format!("~ {}: {} -> {}", change.key, change.old, change.new)),
+        );
+        lines.enumerate().try_for_each(|(i, line)| match i {
+            0 => write!(f, "{line}"),
+            _ => write!(f, "\n{line}"),
+        })
+    }
+}
+
+/// Compares the variables of two env files, such as a staging and a production one
+///
+/// Both are parsed like [`parse_to_map`](crate::parse_to_map), so the values are expanded and the last definition
+/// of a key is compared. Use [`EnvDiff::masked`] to hide the values.
+///
+/// ```rust
+/// let staging = "HOST=staging.local\nDEBUG=1\nWORKERS=2";
+/// let prod = "HOST=example.com\nWORKERS=2\nREPLICAS=3";
+///
+/// let diff = simple_env_load::diff(staging, prod);
+/// assert_eq!(diff.removed, [("DEBUG".into(), "1".into())]);
+/// assert_eq!(diff.added, [("REPLICAS".into(), "3".into())]);
+/// assert_eq!(diff.changed[0].new, "example.com");
+///
+/// assert_eq!(
+///     diff.masked().to_string(),
+///     "- DEBUG=****\n+ REPLICAS=****\n~ HOST: **** -> ****"
+/// );
+/// ```
+pub fn diff(a: &str, b: &str) -> EnvDiff {
+    let (a, b) = (crate::parse_to_map(a), crate::parse_to_map(b));
+    let find = |vars: &[(String, String)], key: &str| {
+        vars.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.clone())
+    };
+
+    let mut diff = EnvDiff::default();
+    for (key, old) in &a {
+        match find(&b, key) {
+            Some(new) if new != *old => diff.changed.push(ChangedValue {
+                key: key.clone(),
+                old: old.clone(),
+                new,
+            }),
+            Some(..) => {}
+            None => diff.removed.push((key.clone(), old.clone())),
+        }
+    }
+    diff.added = b
+        .into_iter()
+        .filter(|(key, _)| find(&a, key).is_none())
+        .collect();
+    diff
+}
+
+#[test]
+fn diff_env_files() {
+    assert!(diff("A=1\nA=2", "A=2").is_empty());
+    assert_eq!(diff("", "").to_string(), "");
+
+    let diff = diff("A=1\nB=\nC=3", "C=4\nB=x\nD=");
+    let keys = diff.changed.iter().map(|change| &*change.key);
+    assert_eq!(keys.collect::<Vec<_>>(), ["B", "C"]);
+    assert_eq!(
+        diff.clone().to_string(),
+        "- A=1\n+ D=\n~ B:  -> x\n~ C: 3 -> 4"
+    );
+    assert_eq!(
+        diff.masked().to_string(),
+        "- A=****\n+ D=\n~ B:  -> ****\n~ C: **** -> ****"
+    );
+}
@@ -0,0 +1,229 @@
+use std::ops::Range;
+
+use crate::{parse, write};
+
+/// An env file that keeps its comments, blank lines and formatting, for editing it in place
+///
+/// Lines that aren't touched are written back exactly as they were read.
+///
+/// ```rust
+/// use simple_env_load::EnvDocument;
+///
+/// let data = "# the database\nexport DB_HOST=localhost # for now\nDB_USER=admin\n";
+/// let mut doc = EnvDocument::parse(data);
+///
+/// doc.set("DB_HOST", "db.example.com");
+/// doc.rename("DB_USER", "DB_USERNAME");
+/// doc.set("DB_PASSWORD", "hunter 2");
+///
+/// assert_eq!(
+///     doc.to_string(),
+///     "# the database\nexport DB_HOST=db.example.com # for now\nDB_USERNAME=admin\nDB_PASSWORD='hunter 2'\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvDocument {
+    items: Vec<Item>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Item {
+    text: String,
+    entry: Option<Spans>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Spans {
+    key: Range<usize>,
+    value: Range<usize>,
+    parsed: String,
+    // a `KEY<<EOF` block's value spans from the key to its delimiter, so setting it needs the `=`
+    heredoc: bool,
+}
+
+impl EnvDocument {
+    /// Parse a document, this never fails: lines that can't be parsed are kept as is
+    pub fn parse(data: &str) -> Self {
+        let items = parse::chunks(data, Default::default())
+            .map(|(text, entry)| Item::new(text, entry.ok().flatten()))
+            .collect();
+        Self { items }
+    }
+
+    /// The value a key was last defined as
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries()
+            .filter(|(k, _)| *k == key)
+            .map(|(_, v)| v)
+            .last()
+    }
+
+    /// Every definition in the document, in order
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.items.iter().filter_map(|item| {
+            let spans = item.entry.as_ref()?;
+            Some((&item.text[spans.key.clone()], &*spans.parsed))
+        })
+    }
+
+    /// Set a key, replacing the value of its last definition, or appending it to the end of the document
+    ///
+    /// The value is quoted as needed, see [`write_env`](crate::write_env)
+    ///
+    /// # Panics
+    /// If the key is empty or contains whitespace, `=` or `#`
+    pub fn set(&mut self, key: &str, value: &str) {
+        assert!(write::valid_key(key), "'{key}' can't be written as a key");
+
+        let value = write::quote(value);
+        if let Some(item) = self.position(key).map(|i| &mut self.items[i]) {
+            let spans = item.entry.as_ref();
+            if let Some((range, heredoc)) = spans.map(|spans| (spans.value.clone(), spans.heredoc))
+            {
+                match heredoc {
+                    true => item.text.replace_range(range, &format!("={value}")),
+                    false => item.text.replace_range(range, &value),
+                }
+                item.reparse();
+            }
+            return;
+        }
+
+        if let Some(last) = self.items.last_mut() {
+            if !last.text.ends_with('\n') {
+                last.text.push('\n');
+            }
+        }
+        self.items.push(Item::from_text(format!("{key}={value}\n")));
+    }
+
+    /// Remove every definition of a key, returning the value it was last defined as
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let value = self.get(key).map(ToString::to_string);
+        self.items.retain(|item| item.key() != Some(key));
+        value
+    }
+
+    /// Rename every definition of a key, returning whether there were any
+    ///
+    /// # Panics
+    /// If the new key is empty or contains whitespace, `=` or `#`
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        assert!(write::valid_key(to), "'{to}' can't be written as a key");
+
+        let mut found = false;
+        for item in self
+            .items
+            .iter_mut()
+            .filter(|item| item.key() == Some(from))
+        {
+            let range = item.entry.as_ref().map(|spans| spans.key.clone());
+            if let Some(range) = range {
+                item.text.replace_range(range, to);
+                item.reparse();
+                found = true;
+            }
+        }
+        found
+    }
+
+    /// Atomically replace the file at `path` with this document, see [`write_file_atomic`](crate::write_file_atomic)
+    pub fn save(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        mode: Option<u32>,
+    ) -> std::io::Result<()> {
+        write::write_file_atomic(path, self.to_string(), mode)
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        self.items.iter().rposition(|item| item.key() == Some(key))
+    }
+}
+
+impl std::str::FromStr for EnvDocument {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(s))
+    }
+}
+
+impl std::fmt::Display for EnvDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.items
+            .iter()
+            .try_for_each(|item| f.write_str(&item.text))
+    }
+}
+
+impl Item {
+    fn new(text: &str, entry: Option<parse::Entry<'_>>) -> Self {
+        let offset = |s: &str| s.as_ptr() as usize - text.as_ptr() as usize;
+        let entry = entry.map(|entry| {
+            let key = offset(entry.key)..offset(entry.key) + entry.key.len();
+            let mut value = offset(entry.raw)..offset(entry.raw) + entry.raw.len();
+            match entry.quote {
+                Some(parse::Quote::Single | parse::Quote::Double) => {
+                    value = value.start - 1..value.end + 1
+                }
+                Some(parse::Quote::Heredoc { .. }) => value = key.end..text.trim_end().len(),
+                _ => {}
+            }
+            Spans {
+                heredoc: matches!(entry.quote, Some(parse::Quote::Heredoc { .. })),
+                key,
+                value,
+                parsed: entry.value.into_owned(),
+            }
+        });
+
+        Self {
+            text: text.to_string(),
+            entry,
+        }
+    }
+
+    fn from_text(text: String) -> Self {
+        let entry = parse::chunks(&text, Default::default())
+            .next()
+            .and_then(|(_, entry)| entry.ok().flatten());
+        Self::new(&text, entry)
+    }
+
+    fn reparse(&mut self) {
+        *self = Self::from_text(std::mem::take(&mut self.text));
+    }
+
+    fn key(&self) -> Option<&str> {
+        self.entry
+            .as_ref()
+            .map(|spans| &self.text[spans.key.clone()])
+    }
+}
+
+#[test]
+fn document_round_trip() {
+    let data = "  # comment\r\n\nKEY = \"multi\nline\" # trailing\nbroken line\nexport OTHER='x'\nKEY=second";
+    let mut doc = EnvDocument::parse(data);
+    assert_eq!(doc.to_string(), data);
+    assert_eq!(doc.get("KEY"), Some("second"));
+
+    doc.set("OTHER", "it's");
+    assert_eq!(doc.get("OTHER"), Some("it's"));
+    assert!(doc.to_string().contains("export OTHER=\"it's\"\n"));
+
+    assert_eq!(doc.remove("KEY"), Some("second".into()));
+    assert_eq!(doc.get("KEY"), None);
+
+    doc.set("NEW", "1");
+    assert_eq!(
+        doc.to_string(),
+        "  # comment\r\n\nbroken line\nexport OTHER=\"it's\"\nNEW=1\n"
+    );
+    assert!(!doc.rename("MISSING", "X"));
+
+    let mut doc = EnvDocument::parse("PEM<<EOF\na\nb\nEOF\nNEXT=1\n");
+    assert_eq!(doc.get("PEM"), Some("a\nb"));
+    doc.set("PEM", "c");
+    assert_eq!(doc.to_string(), "PEM=c\nNEXT=1\n");
+}
@@ -0,0 +1,54 @@
+use std::io;
+
+use crate::parse::BOM;
+
+/// Decodes the contents of a file, which is UTF-8 unless it starts with a UTF-16 byte order mark and `utf16` is set
+///
+/// A UTF-8 byte order mark is removed.
+pub(crate) fn decode(data: Vec<u8>, utf16: bool) -> io::Result<String> {
+    let invalid = |what| io::Error::new(io::ErrorKind::InvalidData, what);
+    let from_utf16 = |data: &[u8], unit: fn([u8; 2]) -> u16| {
+        let pairs = data.chunks_exact(2);
+        if !pairs.remainder().is_empty() {
+            return Err(invalid("stream did not contain valid UTF-16"));
+        }
+        let units = pairs.map(|pair| unit([pair[0], pair[1]]));
+        char::decode_utf16(units)
+            .collect::<Result<String, _>>()
+            .map_err(|_| invalid("stream did not contain valid UTF-16"))
+    };
+
+    let data = match data.as_slice() {
+        [0xff, 0xfe, rest @ ..] if utf16 => from_utf16(rest, u16::from_le_bytes)?,
+        [0xfe, 0xff, rest @ ..] if utf16 => from_utf16(rest, u16::from_be_bytes)?,
+        _ => String::from_utf8(data).map_err(|_| invalid("stream did not contain valid UTF-8"))?,
+    };
+    Ok(match data.strip_prefix(BOM) {
+        Some(rest) => rest.to_string(),
+        None => data,
+    })
+}
+
+#[test]
+fn decode_encodings() {
+    let utf16 = |bom: [u8; 2], unit: fn(u16) -> [u8; 2]| {
+        let units = "A=\u{e9}\r\nB=\u{1f600}".encode_utf16().flat_map(unit);
+        bom.into_iter().chain(units).collect::<Vec<_>>()
+    };
+    let le = utf16([0xff, 0xfe], u16::to_le_bytes);
+    let be = utf16([0xfe, 0xff], u16::to_be_bytes);
+
+    assert_eq!(decode(b"\xef\xbb\xbfA=1".to_vec(), true).unwrap(), "A=1");
+    assert_eq!(decode(le.clone(), true).unwrap(), "A=\u{e9}\r\nB=\u{1f600}");
+    assert_eq!(decode(be, true).unwrap(), "A=\u{e9}\r\nB=\u{1f600}");
+    assert_eq!(decode(b"A=1".to_vec(), true).unwrap(), "A=1");
+
+    assert!(decode(le.clone(), false).is_err());
+    assert!(decode(le[..le.len() - 1].to_vec(), true).is_err());
+    assert!(decode(vec![0xff, 0xfe, 0x00, 0xd8], true).is_err());
+    assert!(decode(b"A=\xff".to_vec(), true).is_err());
+
+    let entries = crate::parse_iter("\u{feff}A=1\nB=2").collect::<Vec<_>>();
+    assert_eq!((entries[0].key, entries[0].span.clone()), ("A", 3..6));
+    assert_eq!(entries[1].key, "B");
+}
@@ -0,0 +1,581 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    str::FromStr,
+    sync::Mutex,
+    time::Duration,
+};
+
+use crate::{EnvLoadError, LoadReport};
+
+/// Loaded variables, with typed accessors
+///
+/// Missing keys are [`EnvLoadError::Missing`], values that can't be converted are [`EnvLoadError::Invalid`].
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// let env: simple_env_load::Env = "PORT=8080\nDEBUG=yes\nTIMEOUT=1m30s\nCACHE_SIZE=512MiB".parse().unwrap();
+///
+/// assert_eq!(env.get::<u16>("PORT").unwrap(), 8080);
+/// assert_eq!(env.get_bool("DEBUG").unwrap(), true);
+/// assert_eq!(env.get_duration("TIMEOUT").unwrap(), Duration::from_secs(90));
+/// assert_eq!(env.get_bytes("CACHE_SIZE").unwrap(), 512 * 1024 * 1024);
+/// assert_eq!(env.get_or("WORKERS", 4).unwrap(), 4);
+/// assert!(env.get::<u16>("MISSING").is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Env {
+    vars: HashMap<String, String>,
+}
+
+impl Env {
+    /// The variables of the current process
+    pub fn from_process() -> Self {
+        std::env::vars().collect()
+    }
+
+    /// The value of a key, as is
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.vars.get(key).map(String::as_str)
+    }
+
+    /// Parse the value of a key with [`FromStr`]
+    pub fn get<T>(&self, key: &str) -> Result<T, EnvLoadError>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.convert(key, |value| {
+            value.trim().parse().map_err(|err: T::Err| err.to_string())
+        })
+    }
+
+    /// Parse the value of a key with [`FromStr`], or use `default` if it's missing
+    pub fn get_or<T>(&self, key: &str, default: T) -> Result<T, EnvLoadError>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        match self.vars.contains_key(key) {
+            true => self.get(key),
+            false => Ok(default),
+        }
+    }
+
+    /// The value of a key as a boolean: `true`/`false`, `1`/`0`, `yes`/`no` or `on`/`off`, ignoring case
+    pub fn get_bool(&self, key: &str) -> Result<bool, EnvLoadError> {
+        self.convert(key, parse_bool)
+    }
+
+    /// The value of a key as a duration, e.g. `30s`, `5m` or `1h30m`
+    ///
+    /// The units are `ms`, `s`, `m`, `h` and `d`, a number without a unit is in seconds.
+    pub fn get_duration(&self, key: &str) -> Result<Duration, EnvLoadError> {
+        self.convert(key, parse_duration)
+    }
+
+    /// The value of a key as a number of bytes, e.g. `512MB` or `1GiB`
+    ///
+    /// `KB`, `MB`, `GB` and `TB` are powers of 1000, `KiB`, `MiB`, `GiB` and `TiB` are powers of 1024.
+    /// Units ignore case, and a number without a unit is in bytes.
+    pub fn get_bytes(&self, key: &str) -> Result<u64, EnvLoadError> {
+        self.convert(key, parse_bytes)
+    }
+
+    /// The value of a key split on `delimiter`, e.g. `HOSTS=a,b,c` with `','`
+    ///
+    /// Items are trimmed, and empty items are skipped.
+    pub fn get_list(&self, key: &str, delimiter: char) -> Result<Vec<String>, EnvLoadError> {
+        self.convert(key, |value| Ok(split_list(value, delimiter)))
+    }
+
+    /// The value of a key as a map, e.g. `LIMITS=cpu=2;memory=512` with `';'` and `'='`
+    ///
+    /// Keys and values are trimmed, empty items are skipped, and an item without `assign` is an error.
+    ///
+    /// ```rust
+    /// let env: simple_env_load::Env = "LIMITS = 'cpu=2; memory=512'".parse().unwrap();
+    /// let limits = env.get_map("LIMITS", ';', '=').unwrap();
+    /// assert_eq!(limits["memory"], "512");
+    /// ```
+    pub fn get_map(
+        &self,
+        key: &str,
+        delimiter: char,
+        assign: char,
+    ) -> Result<HashMap<String, String>, EnvLoadError> {
+        self.convert(key, |value| {
+            split_list(value, delimiter)
+                .into_iter()
+                .map(|item| match item.split_once(assign) {
+                    Some((k, v)) => Ok((k.trim().to_string(), v.trim().to_string())),
+                    None => Err(format!("expected '{assign}' in '{item}'")),
+                })
+                .collect()
+        })
+    }
+
+    /// The value of a key as a socket address, e.g. `127.0.0.1:8080` or `[::1]:8080`
+    ///
+    /// ```rust
+    /// let env: simple_env_load::Env = "LISTEN=0.0.0.0:8080\nBIND=localhost".parse().unwrap();
+    /// assert_eq!(env.get_socket_addr("LISTEN").unwrap().port(), 8080);
+    ///
+    /// let err = env.get_socket_addr("BIND").unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "invalid value 'localhost' for 'BIND': expected an IP address and a port, e.g. 127.0.0.1:8080"
+    /// );
+    /// ```
+    pub fn get_socket_addr(&self, key: &str) -> Result<SocketAddr, EnvLoadError> {
+        self.convert(key, |value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| "expected an IP address and a port, e.g. 127.0.0.1:8080".into())
+        })
+    }
+
+    /// The value of a key as an IP address, e.g. `127.0.0.1` or `::1`
+    pub fn get_ip_addr(&self, key: &str) -> Result<IpAddr, EnvLoadError> {
+        self.convert(key, |value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| "expected an IP address, e.g. 127.0.0.1 or ::1".into())
+        })
+    }
+
+    /// The value of a key as a path, a leading `~` is expanded to the home directory
+    ///
+    /// An empty value is an error.
+    pub fn get_path(&self, key: &str) -> Result<PathBuf, EnvLoadError> {
+        self.convert(key, |value| {
+            let value = value.trim();
+            match value {
+                "" => Err("expected a path".into()),
+                _ => Ok(crate::path::expand_home(value).into()),
+            }
+        })
+    }
+
+    /// The value of a key as a URL, e.g. `postgres://localhost/app`
+    ///
+    /// Values without a `://`, like `localhost:8080`, are an error.
+    ///
+    /// ```rust
+    /// let env: simple_env_load::Env = "DATABASE_URL=postgres://localhost:5432/app".parse().unwrap();
+    /// let url = env.get_url("DATABASE_URL").unwrap();
+    /// assert_eq!((url.scheme(), url.port()), ("postgres", Some(5432)));
+    /// ```
+    #[cfg(feature = "url")]
+    pub fn get_url(&self, key: &str) -> Result<url::Url, EnvLoadError> {
+        self.convert(key, |value| match url::Url::parse(value.trim()) {
+            // `localhost:8080` would otherwise be a URL with a `localhost` scheme
+            Ok(url) if url.cannot_be_a_base() => {
+                Err("expected a URL like scheme://host/path".into())
+            }
+            url => url.map_err(|err| err.to_string()),
+        })
+    }
+
+    /// Deserialize the value of a key from JSON, e.g. `FEATURES={"beta": true}`
+    ///
+    /// ```rust
+    /// #[derive(serde::Deserialize)]
+    /// struct Features {
+    ///     beta: bool,
+    ///     regions: Vec<String>,
+    /// }
+    ///
+    /// let env: simple_env_load::Env = r#"FEATURES='{"beta": true, "regions": ["eu"]}'"#.parse().unwrap();
+    /// let features = env.get_json::<Features>("FEATURES").unwrap();
+    /// assert!(features.beta);
+    /// assert_eq!(features.regions, ["eu"]);
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn get_json<T>(&self, key: &str) -> Result<T, EnvLoadError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.convert(key, |value| {
+            serde_json::from_str(value).map_err(|err| err.to_string())
+        })
+    }
+
+    fn convert<T>(
+        &self,
+        key: &str,
+        convert: impl FnOnce(&str) -> Result<T, String>,
+    ) -> Result<T, EnvLoadError> {
+        let value = self.get_str(key).ok_or_else(|| EnvLoadError::Missing {
+            key: key.to_string(),
+        })?;
+        convert(value).map_err(|reason| EnvLoadError::Invalid {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason,
+        })
+    }
+}
+
+/// An [`Env`] that records the keys its getters read, to find the variables nothing uses
+///
+/// The getters are the same as [`Env`]'s. Keys are recorded whether they're set or not.
+///
+/// ```rust
+/// let env = "HOST=localhost\nPORT=8080\nLEGACY_FLAG=1".parse::<simple_env_load::Env>().unwrap();
+/// let env = env.tracked();
+///
+/// assert_eq!(env.get_str("HOST"), Some("localhost"));
+/// assert_eq!(env.get_or("PORT", 80).unwrap(), 8080);
+/// assert!(env.get_bool("DEBUG").is_err());
+///
+/// // e.g. at shutdown
+/// for key in env.unused() {
+///     eprintln!("{key} is set, but was never read");
+/// }
+/// assert_eq!(env.unused(), ["LEGACY_FLAG"]);
+/// ```
+#[derive(Debug, Default)]
+pub struct TrackedEnv {
+    env: Env,
+    read: Mutex<BTreeSet<String>>,
+}
+
+impl Env {
+    /// Record the keys that are read from now on, see [`TrackedEnv`]
+    pub fn tracked(self) -> TrackedEnv {
+        TrackedEnv {
+            env: self,
+            read: Mutex::default(),
+        }
+    }
+}
+
+impl TrackedEnv {
+    /// The keys that are set but were never read, sorted
+    pub fn unused(&self) -> Vec<&str> {
+        let read = self.read.lock().unwrap_or_else(|err| err.into_inner());
+        let mut unused = (self.env.vars.keys())
+            .filter(|key| !read.contains(*key))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        unused.sort_unstable();
+        unused
+    }
+
+    /// The keys that were read, set or not, sorted
+    pub fn read_keys(&self) -> Vec<String> {
+        let read = self.read.lock().unwrap_or_else(|err| err.into_inner());
+        read.iter().cloned().collect()
+    }
+
+    /// The variables, without the tracking
+    pub fn into_inner(self) -> Env {
+        self.env
+    }
+
+    fn track(&self, key: &str) -> &Env {
+        let mut read = self.read.lock().unwrap_or_else(|err| err.into_inner());
+        if !read.contains(key) {
+            read.insert(key.to_string());
+        }
+        &self.env
+    }
+
+    /// See [`Env::get_str`]
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.track(key).get_str(key)
+    }
+
+    /// See [`Env::get`]
+    pub fn get<T>(&self, key: &str) -> Result<T, EnvLoadError>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.track(key).get(key)
+    }
+
+    /// See [`Env::get_or`]
+    pub fn get_or<T>(&self, key: &str, default: T) -> Result<T, EnvLoadError>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.track(key).get_or(key, default)
+    }
+
+    /// See [`Env::get_bool`]
+    pub fn get_bool(&self, key: &str) -> Result<bool, EnvLoadError> {
+        self.track(key).get_bool(key)
+    }
+
+    /// See [`Env::get_duration`]
+    pub fn get_duration(&self, key: &str) -> Result<Duration, EnvLoadError> {
+        self.track(key).get_duration(key)
+    }
+
+    /// See [`Env::get_bytes`]
+    pub fn get_bytes(&self, key: &str) -> Result<u64, EnvLoadError> {
+        self.track(key).get_bytes(key)
+    }
+
+    /// See [`Env::get_list`]
+    pub fn get_list(&self, key: &str, delimiter: char) -> Result<Vec<String>, EnvLoadError> {
+        self.track(key).get_list(key, delimiter)
+    }
+
+    /// See [`Env::get_map`]
+    pub fn get_map(
+        &self,
+        key: &str,
+        delimiter: char,
+        assign: char,
+    ) -> Result<HashMap<String, String>, EnvLoadError> {
+        self.track(key).get_map(key, delimiter, assign)
+    }
+
+    /// See [`Env::get_socket_addr`]
+    pub fn get_socket_addr(&self, key: &str) -> Result<SocketAddr, EnvLoadError> {
+        self.track(key).get_socket_addr(key)
+    }
+
+    /// See [`Env::get_ip_addr`]
+    pub fn get_ip_addr(&self, key: &str) -> Result<IpAddr, EnvLoadError> {
+        self.track(key).get_ip_addr(key)
+    }
+
+    /// See [`Env::get_path`]
+    pub fn get_path(&self, key: &str) -> Result<PathBuf, EnvLoadError> {
+        self.track(key).get_path(key)
+    }
+
+    /// See [`Env::get_url`]
+    #[cfg(feature = "url")]
+    pub fn get_url(&self, key: &str) -> Result<url::Url, EnvLoadError> {
+        self.track(key).get_url(key)
+    }
+
+    /// See [`Env::get_json`]
+    #[cfg(feature = "serde_json")]
+    pub fn get_json<T>(&self, key: &str) -> Result<T, EnvLoadError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.track(key).get_json(key)
+    }
+}
+
+impl From<Env> for TrackedEnv {
+    fn from(env: Env) -> Self {
+        env.tracked()
+    }
+}
+
+impl FromStr for Env {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(crate::parse_to_map(s).into_iter().collect())
+    }
+}
+
+impl From<LoadReport> for Env {
+    fn from(report: LoadReport) -> Self {
+        report.into_pairs().into_iter().collect()
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Env {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            vars: iter
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        }
+    }
+}
+
+pub(crate) fn parse_bool(value: &str) -> Result<bool, String> {
+    match &*value.trim().to_ascii_lowercase() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" => Ok(false),
+        _ => Err("expected a boolean".into()),
+    }
+}
+
+fn split_list(value: &str, delimiter: char) -> Vec<String> {
+    value
+        .split(delimiter)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Splits `value` into (number, unit) pairs, e.g. `1h30m` is `[(1, "h"), (30, "m")]`
+fn quantities(value: &str) -> Result<Vec<(f64, String)>, String> {
+    let mut out = vec![];
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return Err("expected a number".into());
+    }
+
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number = rest[..end]
+            .parse::<f64>()
+            .map_err(|_| format!("expected a number at '{rest}'"))?;
+        rest = rest[end..].trim_start();
+
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        out.push((number, rest[..end].to_ascii_lowercase()));
+        rest = rest[end..].trim_start();
+    }
+    Ok(out)
+}
+
+pub(crate) fn parse_duration(value: &str) -> Result<Duration, String> {
+    let mut secs = 0.0;
+    for (number, unit) in quantities(value)? {
+        secs += number
+            * match &*unit {
+                "ms" => 0.001,
+                "" | "s" => 1.0,
+                "m" => 60.0,
+                "h" => 60.0 * 60.0,
+                "d" => 24.0 * 60.0 * 60.0,
+                unit => return Err(format!("unknown unit '{unit}'")),
+            };
+    }
+    Duration::try_from_secs_f64(secs).map_err(|err| err.to_string())
+}
+
+pub(crate) fn parse_bytes(value: &str) -> Result<u64, String> {
+    let mut bytes = 0.0;
+    for (number, unit) in quantities(value)? {
+        bytes += number
+            * match &*unit {
+                "" | "b" => 1.0,
+                "k" | "kb" => 1e3,
+                "m" | "mb" => 1e6,
+                "g" | "gb" => 1e9,
+                "t" | "tb" => 1e12,
+                "ki" | "kib" => 1024f64,
+                "mi" | "mib" => 1024f64.powi(2),
+                "gi" | "gib" => 1024f64.powi(3),
+                "ti" | "tib" => 1024f64.powi(4),
+                unit => return Err(format!("unknown unit '{unit}'")),
+            };
+    }
+    match bytes <= u64::MAX as f64 {
+        true => Ok(bytes.round() as u64),
+        false => Err("too many bytes".into()),
+    }
+}
+
+#[test]
+fn env_conversions() {
+    assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+    assert_eq!(parse_duration("1h 30m"), Ok(Duration::from_secs(5400)));
+    assert_eq!(parse_duration("2.5"), Ok(Duration::from_millis(2500)));
+    assert!(parse_duration("5 weeks").is_err());
+    assert!(parse_duration("").is_err());
+
+    assert_eq!(parse_bytes("1kb"), Ok(1000));
+    assert_eq!(parse_bytes("1.5 KiB"), Ok(1536));
+    assert_eq!(parse_bytes("2GB"), Ok(2_000_000_000));
+    assert!(parse_bytes("lots").is_err());
+
+    let env = [("FLAG", "maybe"), ("HOSTS", " a, b,,c "), ("MAP", "a=1|b")]
+        .into_iter()
+        .collect::<Env>();
+    assert_eq!(env.get_list("HOSTS", ',').unwrap(), ["a", "b", "c"]);
+    assert!(env.get_map("MAP", '|', '=').is_err());
+
+    let err = env.get_bool("FLAG").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value 'maybe' for 'FLAG': expected a boolean"
+    );
+
+    let env = [
+        ("IP", " ::1 "),
+        ("ADDR", "[::1]:80"),
+        ("DIR", " ~/data "),
+        ("EMPTY", " "),
+    ]
+    .into_iter()
+    .collect::<Env>();
+    assert_eq!(
+        env.get_ip_addr("IP").unwrap(),
+        IpAddr::from([0, 0, 0, 0, 0, 0, 0, 1])
+    );
+    assert_eq!(env.get_socket_addr("ADDR").unwrap().port(), 80);
+    assert!(env.get_ip_addr("ADDR").is_err());
+    assert!(env.get_path("DIR").unwrap().ends_with("data"));
+    assert!(env.get_path("DIR").unwrap().is_absolute());
+    assert!(!env.get_path("DIR").unwrap().starts_with("~"));
+    let err = env.get_path("EMPTY").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value ' ' for 'EMPTY': expected a path"
+    );
+
+    #[cfg(feature = "url")]
+    {
+        let env = [
+            ("A", "localhost:8080/app"),
+            ("B", "http://[::1"),
+            ("C", "file:///tmp"),
+        ]
+        .into_iter()
+        .collect::<Env>();
+        assert_eq!(
+            env.get_url("A").unwrap_err().to_string(),
+            "invalid value 'localhost:8080/app' for 'A': expected a URL like scheme://host/path"
+        );
+        assert_eq!(
+            env.get_url("B").unwrap_err().to_string(),
+            "invalid value 'http://[::1' for 'B': invalid IPv6 address"
+        );
+        assert_eq!(env.get_url("C").unwrap().path(), "/tmp");
+    }
+
+    #[cfg(feature = "serde_json")]
+    {
+        let env = [("IDS", "[1, 2]"), ("BAD", "{")]
+            .into_iter()
+            .collect::<Env>();
+        assert_eq!(env.get_json::<Vec<u8>>("IDS").unwrap(), [1, 2]);
+        let err = env.get_json::<Vec<u8>>("BAD").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value '{' for 'BAD': invalid type: map, expected a sequence at line 1 column 0"
+        );
+        assert!(env.get_json::<u8>("MISSING").is_err());
+    }
+}
+
+#[test]
+fn track_reads() {
+    let env = [("A", "1"), ("B", "x"), ("C", "")]
+        .into_iter()
+        .collect::<Env>();
+    let env = TrackedEnv::from(env);
+    assert_eq!(env.unused(), ["A", "B", "C"]);
+
+    assert!(env.get::<u8>("B").is_err());
+    assert_eq!(env.get_list("MISSING", ',').ok(), None);
+    assert_eq!(env.unused(), ["A", "C"]);
+    assert_eq!(env.read_keys(), ["B", "MISSING"]);
+    assert_eq!(env.into_inner().get_str("A"), Some("1"));
+}
@@ -0,0 +1,236 @@
+use std::path::Path;
+
+use crate::{interpolate, parse, LoadError};
+
+/// A type that can be built from env variables, usually with `#[derive(EnvLoad)]`
+///
+/// The derive macro needs the `derive` feature. Each field is read from the key of the same name in uppercase,
+/// and parsed with [`FromStr`](std::str::FromStr). Fields without a default are required, unless they are an `Option`.
+///
+/// |Attribute|Effect|
+/// |---|---|
+/// `#[env(prefix = "APP_")]`|on the struct, prepends a prefix to every key
+/// `#[env(rename = "KEY")]`|on a field, reads it from `KEY` instead (the prefix still applies)
+/// `#[env(default = "value")]`|on a field, is parsed when the key is missing
+///
+/// ```rust
+/// # #[cfg(feature = "derive")] {
+/// use simple_env_load::EnvLoad;
+///
+/// #[derive(Debug, EnvLoad)]
+/// #[env(prefix = "APP_")]
+/// struct Config {
+///     #[env(rename = "DB_URL")]
+///     database_url: String,
+///     #[env(default = "8080")]
+///     port: u16,
+///     debug: Option<bool>,
+/// }
+///
+/// let config = Config::from_env_str("APP_DB_URL=postgres://localhost/app").unwrap();
+/// assert_eq!(config.database_url, "postgres://localhost/app");
+/// assert_eq!((config.port, config.debug), (8080, None));
+///
+/// let err = Config::from_env_str("APP_PORT=http").unwrap_err();
+/// assert_eq!(err.key(), Some("APP_DB_URL"));
+/// # }
+/// ```
+pub trait EnvLoad: Sized {
+    /// Build this from a function that looks up the value of a key
+    fn from_lookup<F>(lookup: F) -> Result<Self, EnvLoadError>
+    where
+        F: Fn(&str) -> Option<String>;
+
+    /// Build this from the process environment
+    fn from_env() -> Result<Self, EnvLoadError> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Build this from an env string, see [`parse_to_map`](crate::parse_to_map)
+    fn from_env_str(data: &str) -> Result<Self, EnvLoadError> {
+        let pairs = crate::parse_to_map(data);
+        Self::from_lookup(|key| {
+            pairs
+                .iter()
+                .find_map(|(k, v)| (k == key).then(|| v.clone()))
+        })
+    }
+
+    /// Build this from env files, later files take precedence over earlier ones
+    ///
+    /// The files are only read, the process environment isn't changed.
+    fn from_files<I, T>(paths: I) -> Result<Self, EnvLoadError>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<Path>,
+    {
+        let mut files = vec![];
+        for path in paths {
+            let path = crate::path::expand(path.as_ref());
+            let data =
+                std::fs::read_to_string(&path).map_err(|error| LoadError::Io { path, error })?;
+            files.push(data);
+        }
+
+        let pairs = files
+            .iter()
+            .flat_map(|data| parse::parse(data, Default::default()))
+            .collect::<Vec<_>>();
+        let values = interpolate::resolve(&pairs)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(LoadError::from)?;
+
+        Self::from_lookup(|key| {
+            values
+                .iter()
+                .rev()
+                .find_map(|(k, v)| (k == key).then(|| v.clone()))
+        })
+    }
+}
+
+/// An error produced by [`EnvLoad`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EnvLoadError {
+    /// A required key wasn't set
+    Missing {
+        /// The key that was looked up
+        key: String,
+    },
+    /// A value couldn't be parsed
+    Invalid {
+        /// The key that was looked up
+        key: String,
+        /// Its value
+        value: String,
+        /// Why it couldn't be parsed
+        reason: String,
+    },
+    /// A file couldn't be loaded
+    Load(LoadError),
+}
+
+impl EnvLoadError {
+    /// The key that caused this error, if it was caused by a key
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Self::Missing { key } | Self::Invalid { key, .. } => Some(key),
+            Self::Load(..) => None,
+        }
+    }
+}
+
+impl From<LoadError> for EnvLoadError {
+    fn from(err: LoadError) -> Self {
+        Self::Load(err)
+    }
+}
+
+impl std::fmt::Display for EnvLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { key } => write!(f, "'{key}' is not set"),
+            Self::Invalid { key, value, reason } => {
+                write!(f, "invalid value '{value}' for '{key}': {reason}")
+            }
+            Self::Load(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for EnvLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Load(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Used by the code generated by `#[derive(EnvLoad)]`
+#[doc(hidden)]
+pub mod __private {
+    use super::EnvLoadError;
+
+    pub fn required<T>(
+        lookup: &impl Fn(&str) -> Option<String>,
+        key: &str,
+        default: Option<&str>,
+    ) -> Result<T, EnvLoadError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        optional(lookup, key, default)?.ok_or_else(|| EnvLoadError::Missing {
+            key: key.to_string(),
+        })
+    }
+
+    pub fn optional<T>(
+        lookup: &impl Fn(&str) -> Option<String>,
+        key: &str,
+        default: Option<&str>,
+    ) -> Result<Option<T>, EnvLoadError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let value = match lookup(key).or_else(|| default.map(ToString::to_string)) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        value
+            .parse()
+            .map(Some)
+            .map_err(|err: T::Err| EnvLoadError::Invalid {
+                key: key.to_string(),
+                reason: err.to_string(),
+                value,
+            })
+    }
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_env_load() {
+    use crate::EnvLoad;
+
+    #[derive(Debug, PartialEq, EnvLoad)]
+    struct Config {
+        name: String,
+        #[env(default = "3")]
+        retries: u8,
+        #[env(rename = "TIMEOUT_SECS")]
+        timeout: Option<f32>,
+    }
+
+    let config = Config::from_lookup(|key| match key {
+        "NAME" => Some("app".into()),
+        "TIMEOUT_SECS" => Some("1.5".into()),
+        _ => None,
+    });
+    let expected = Config {
+        name: "app".into(),
+        retries: 3,
+        timeout: Some(1.5),
+    };
+    assert_eq!(config.unwrap(), expected);
+
+    let err = Config::from_env_str("NAME=app\nRETRIES=many").unwrap_err();
+    assert!(matches!(&err, EnvLoadError::Invalid { value, .. } if value == "many"));
+
+    let err = Config::from_env_str("RETRIES=1").unwrap_err();
+    assert!(matches!(&err, EnvLoadError::Missing { key } if key == "NAME"));
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn load_env_matches_parse_to_map() {
+    const ENV: &[(&str, &str)] = crate::load_env!("fixtures/parse.env");
+    let expected = crate::parse_to_map(include_str!("../fixtures/parse.env"));
+    let env = ENV.iter().map(|&(k, v)| (k.to_string(), v.to_string()));
+    assert_eq!(env.collect::<Vec<_>>(), expected);
+    assert!(ENV.contains(&("PATHS", "a:b:c")));
+}
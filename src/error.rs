@@ -0,0 +1,207 @@
+use crate::{Diagnostic, ExpandError, Origin, SourceError};
+
+/// An error produced by [`Loader::load`](crate::Loader::load) and [`try_load_env_from`](crate::try_load_env_from)
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoadError {
+    /// The file at `path` couldn't be read
+    Io {
+        /// The path that was being read
+        path: std::path::PathBuf,
+        /// The underlying error
+        error: std::io::Error,
+    },
+    /// A line in the file at `path` couldn't be parsed, see [`Loader::strict`](crate::Loader::strict)
+    Parse {
+        /// The path that was being parsed
+        path: std::path::PathBuf,
+        /// What went wrong, and where
+        diagnostic: Diagnostic,
+    },
+    /// The file at `path` is readable by its group or by others, see [`Loader::audit_permissions`](crate::Loader::audit_permissions)
+    InsecurePermissions {
+        /// The offending path
+        path: std::path::PathBuf,
+        /// Its permission bits, e.g. `0o644`
+        mode: u32,
+    },
+    /// A key was defined more than once, see [`DuplicatePolicy::Error`](crate::DuplicatePolicy::Error)
+    Duplicate {
+        /// The name of the variable
+        key: String,
+        /// Where it was first defined
+        first: Origin,
+        /// Where it was defined again
+        second: Origin,
+    },
+    /// A key isn't a valid name, see [`KeyPolicy::Reject`](crate::KeyPolicy::Reject)
+    InvalidKey {
+        /// The key as it was written
+        key: String,
+        /// Where it was defined
+        origin: Origin,
+    },
+    /// Files include each other, see [`Loader::includes`](crate::Loader::includes)
+    IncludeCycle {
+        /// The files in the cycle, in the order they were included
+        paths: Vec<std::path::PathBuf>,
+    },
+    /// Files include each other too deeply, see [`Loader::includes`](crate::Loader::includes)
+    IncludeDepth {
+        /// The path that would have been included
+        path: std::path::PathBuf,
+    },
+    /// The file at `path` couldn't be decrypted, see [`Loader::decrypt`](crate::Loader::decrypt)
+    Decrypt {
+        /// The path that was being decrypted
+        path: std::path::PathBuf,
+        /// Why it couldn't be decrypted
+        reason: String,
+    },
+    /// A value couldn't be expanded
+    Expand(ExpandError),
+    /// The files couldn't be watched, see [`Loader::watch`](crate::Loader::watch)
+    #[cfg(feature = "watch")]
+    Watch(notify::Error),
+    /// A value directive, such as `@keyring:service/account`, couldn't be resolved
+    Value {
+        /// The name of the variable
+        key: String,
+        /// Where it was defined
+        origin: Origin,
+        /// Why it couldn't be resolved
+        reason: String,
+    },
+    /// The file at `path` exceeds one of the [`Limits`](crate::Limits), see [`Loader::limits`](crate::Loader::limits)
+    Limit {
+        /// The offending path
+        path: std::path::PathBuf,
+        /// Which limit it exceeds
+        reason: String,
+    },
+    /// A [`Source`](crate::Source) couldn't be read, see [`Loader::source`](crate::Loader::source)
+    Source {
+        /// The name of the source
+        name: String,
+        /// The underlying error
+        error: SourceError,
+    },
+    /// The env file at `url` couldn't be fetched, see [`Loader::url`](crate::Loader::url)
+    #[cfg(feature = "http")]
+    Remote {
+        /// The url that was being fetched
+        url: String,
+        /// The underlying error
+        error: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl LoadError {
+    /// The path that caused this error, if it was caused by a file
+    pub fn path(&self) -> Option<&std::path::Path> {
+        match self {
+            Self::Io { path, .. }
+            | Self::Parse { path, .. }
+            | Self::InsecurePermissions { path, .. }
+            | Self::Decrypt { path, .. }
+            | Self::Limit { path, .. }
+            | Self::IncludeDepth { path } => Some(path),
+            Self::IncludeCycle { paths } => paths.last().map(|p| &**p),
+            Self::Duplicate { second, .. } => Some(&second.path),
+            Self::Value { origin, .. } | Self::InvalidKey { origin, .. } => Some(&origin.path),
+            _ => None,
+        }
+    }
+
+    /// The kind of io error, e.g. `NotFound` or `PermissionDenied`
+    pub fn kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            Self::Io { error, .. } => Some(error.kind()),
+            Self::Source { error, .. } => error.kind(),
+            _ => None,
+        }
+    }
+
+    /// The line that couldn't be parsed, if this was caused by a malformed line
+    pub fn diagnostic(&self) -> Option<&Diagnostic> {
+        match self {
+            Self::Parse { diagnostic, .. } => Some(diagnostic),
+            _ => None,
+        }
+    }
+}
+
+impl From<ExpandError> for LoadError {
+    fn from(err: ExpandError) -> Self {
+        Self::Expand(err)
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "cannot read '{}': {}", path.display(), error),
+            Self::Parse { path, diagnostic } => write!(f, "{}:{}", path.display(), diagnostic),
+            Self::InsecurePermissions { path, mode } => {
+                write!(
+                    f,
+                    "'{}' is readable by other users (mode {:o})",
+                    path.display(),
+                    mode
+                )
+            }
+            Self::Duplicate { key, first, second } => {
+                write!(f, "'{key}' is defined at {first} and again at {second}")
+            }
+            Self::InvalidKey { key, origin } => write!(f, "'{key}' at {origin} isn't a valid name"),
+            Self::IncludeCycle { paths } => {
+                let paths = paths.iter().map(|p| p.display().to_string());
+                write!(
+                    f,
+                    "files include each other: {}",
+                    paths.collect::<Vec<_>>().join(" -> ")
+                )
+            }
+            Self::IncludeDepth { path } => {
+                write!(
+                    f,
+                    "cannot include '{}': files are nested too deeply",
+                    path.display()
+                )
+            }
+            Self::Decrypt { path, reason } => {
+                write!(f, "cannot decrypt '{}': {}", path.display(), reason)
+            }
+            Self::Expand(err) => err.fmt(f),
+            Self::Value {
+                key,
+                origin,
+                reason,
+            } => write!(f, "cannot resolve '{key}' at {origin}: {reason}"),
+            Self::Limit { path, reason } => {
+                write!(f, "'{}' exceeds a limit: {}", path.display(), reason)
+            }
+            Self::Source { name, error } => write!(f, "cannot read '{name}': {error}"),
+            #[cfg(feature = "watch")]
+            Self::Watch(err) => write!(f, "cannot watch files: {err}"),
+            #[cfg(feature = "http")]
+            Self::Remote { url, error } => write!(f, "cannot fetch '{url}': {error}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            Self::Parse { diagnostic, .. } => Some(diagnostic),
+            Self::Expand(err) => Some(err),
+            Self::Source { error, .. } => Some(error),
+            #[cfg(feature = "http")]
+            Self::Remote { error, .. } => Some(&**error),
+            #[cfg(feature = "watch")]
+            Self::Watch(err) => Some(err),
+            _ => None,
+        }
+    }
+}
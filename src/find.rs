@@ -0,0 +1,52 @@
+use std::path::{Path, PathBuf};
+
+use crate::{LoadError, LoadReport, Loader};
+
+/// Looks for a file named `name` in `start`, then in each of its parents
+///
+/// ```rust
+/// let found = simple_env_load::find(std::env::current_dir().unwrap(), "Cargo.toml");
+/// assert!(found.is_some());
+/// ```
+pub fn find(start: impl AsRef<Path>, name: impl AsRef<Path>) -> Option<PathBuf> {
+    start
+        .as_ref()
+        .ancestors()
+        .map(|dir| dir.join(name.as_ref()))
+        .find(|path| path.is_file())
+}
+
+/// Finds the nearest `.env` starting from the current directory and walking upwards, then loads it
+///
+/// This is like calling [`find_and_load_from`] with [`std::env::current_dir`].
+pub fn find_and_load() -> Result<LoadReport, LoadError> {
+    let dir = std::env::current_dir().map_err(|error| LoadError::Io {
+        path: PathBuf::from("."),
+        error,
+    })?;
+    find_and_load_from(dir)
+}
+
+/// Finds the nearest `.env` starting from `root` and walking upwards, then loads it
+///
+/// If no `.env` could be found, the error's kind is [`NotFound`](std::io::ErrorKind::NotFound).
+///
+/// ```rust
+/// # let root = std::env::temp_dir().join("find_and_load_doc");
+/// # std::fs::create_dir_all(root.join("crates/app")).unwrap();
+/// # std::fs::write(root.join(".env"), "FIND_AND_LOAD_DOC=found").unwrap();
+/// let report = simple_env_load::find_and_load_from(root.join("crates/app")).unwrap();
+/// assert_eq!(report.origin("FIND_AND_LOAD_DOC").unwrap().path, root.join(".env"));
+/// # std::fs::remove_dir_all(&root).unwrap();
+/// ```
+pub fn find_and_load_from(root: impl AsRef<Path>) -> Result<LoadReport, LoadError> {
+    let root = root.as_ref();
+    let path = find(root, ".env").ok_or_else(|| LoadError::Io {
+        path: root.join(".env"),
+        error: std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no .env in this directory or any of its parents",
+        ),
+    })?;
+    Loader::new().path(path).load()
+}
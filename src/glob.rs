@@ -0,0 +1,172 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::LoadError;
+
+/// Expands a pattern into the files it matches, sorted. See [`Loader::glob`](crate::Loader::glob) for the syntax
+///
+/// A pattern whose directory doesn't exist matches nothing.
+pub(crate) fn expand(pattern: &Path) -> Result<Vec<PathBuf>, LoadError> {
+    let mut base = PathBuf::new();
+    let mut components = pattern.components().peekable();
+    while let Some(component) = components.next_if(|c| !is_pattern(c)) {
+        base.push(component);
+    }
+
+    let components = components
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    let components = components.iter().map(|c| &**c).collect::<Vec<_>>();
+
+    let mut out = vec![];
+    walk(&base, &components, &mut out)?;
+    out.sort();
+    out.dedup();
+    Ok(out)
+}
+
+fn is_pattern(component: &Component<'_>) -> bool {
+    let s = component.as_os_str().to_string_lossy();
+    matches!(component, Component::Normal(..)) && s.contains(['*', '?', '['])
+}
+
+fn walk(dir: &Path, components: &[&str], out: &mut Vec<PathBuf>) -> Result<(), LoadError> {
+    let (head, tail) = match components.split_first() {
+        Some(split) => split,
+        None => {
+            if dir.is_file() {
+                out.push(dir.to_path_buf());
+            }
+            return Ok(());
+        }
+    };
+
+    if !head.contains(['*', '?', '[']) {
+        return walk(&dir.join(head), tail, out);
+    }
+
+    if *head == "**" {
+        walk(dir, tail, out)?;
+    }
+
+    let read_dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let entries = match std::fs::read_dir(read_dir) {
+        Ok(entries) => entries,
+        Err(..) if !read_dir.is_dir() => return Ok(()),
+        Err(error) => {
+            return Err(LoadError::Io {
+                path: dir.to_path_buf(),
+                error,
+            })
+        }
+    };
+
+    for entry in entries {
+        let entry = entry.map_err(|error| LoadError::Io {
+            path: dir.to_path_buf(),
+            error,
+        })?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let path = dir.join(&*name);
+
+        if *head == "**" {
+            if !name.starts_with('.') && path.is_dir() {
+                walk(&path, components, out)?;
+            }
+        } else if matches(head, &name) {
+            walk(&path, tail, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Matches a file name against a single pattern component, also used for the patterns of [`RedactedReport`](crate::RedactedReport)
+pub(crate) fn matches(pattern: &str, name: &str) -> bool {
+    fn inner(pattern: &[char], name: &[char]) -> bool {
+        match (pattern.split_first(), name.split_first()) {
+            (None, None) => true,
+            (Some(('*', rest)), _) => {
+                inner(rest, name) || (!name.is_empty() && inner(pattern, &name[1..]))
+            }
+            (Some(('?', rest)), Some((_, name))) => inner(rest, name),
+            (Some(('[', rest)), Some((&c, name))) => match rest.iter().position(|&c| c == ']') {
+                Some(end) if end > 0 => in_class(&rest[..end], c) && inner(&rest[end + 1..], name),
+                _ => c == '[' && inner(rest, name),
+            },
+            (Some((p, rest)), Some((c, name))) => p == c && inner(rest, name),
+            _ => false,
+        }
+    }
+
+    fn in_class(class: &[char], c: char) -> bool {
+        let (negate, class) = match class.split_first() {
+            Some(('!' | '^', rest)) => (true, rest),
+            _ => (false, class),
+        };
+
+        let mut found = false;
+        let mut i = 0;
+        while i < class.len() {
+            if i + 2 < class.len() && class[i + 1] == '-' {
+                found |= (class[i]..=class[i + 2]).contains(&c);
+                i += 3;
+            } else {
+                found |= class[i] == c;
+                i += 1;
+            }
+        }
+        found != negate
+    }
+
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    inner(&pattern, &name)
+}
+
+#[test]
+fn glob_patterns() {
+    assert!(matches("*.env", "base.env"));
+    assert!(!matches("*.env", ".env"));
+    assert!(matches(".env*", ".env.local"));
+    assert!(matches("?0-[a-c].env", "10-b.env"));
+    assert!(!matches("?0-[!a-c].env", "10-b.env"));
+    assert!(!matches("*.env", "base.env.bak"));
+
+    let dir = std::env::temp_dir().join("simple_env_load_glob");
+    let _ = std::fs::remove_dir_all(&dir);
+    for file in [
+        "b.env",
+        "a.env",
+        "a.txt",
+        "nested/deep/c.env",
+        ".hidden/d.env",
+    ] {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+
+    let found = expand(&dir.join("*.env")).unwrap();
+    assert_eq!(found, [dir.join("a.env"), dir.join("b.env")]);
+
+    let found = expand(&dir.join("**/*.env")).unwrap();
+    assert_eq!(
+        found,
+        [
+            dir.join("a.env"),
+            dir.join("b.env"),
+            dir.join("nested/deep/c.env")
+        ]
+    );
+
+    assert!(expand(&dir.join("missing/*.env")).unwrap().is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
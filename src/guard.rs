@@ -0,0 +1,64 @@
+use std::{collections::HashMap, ffi::OsString};
+
+/// Restores the process environment to what it was when this was created, once it's dropped
+///
+/// This is meant for tests that load env fixtures. The process environment is shared by every thread,
+/// so tests that change it can still see each other's changes if they run at the same time.
+///
+/// ```rust
+/// use simple_env_load::EnvGuard;
+///
+/// std::env::set_var("GUARD_DOC_HOST", "localhost");
+/// std::env::set_var("GUARD_DOC_DEBUG", "1");
+/// {
+///     let _guard = EnvGuard::from_env_str("GUARD_DOC_HOST=example.com\nGUARD_DOC_PORT=8080");
+///     std::env::remove_var("GUARD_DOC_DEBUG");
+///     assert_eq!(std::env::var("GUARD_DOC_HOST").unwrap(), "example.com");
+///     assert_eq!(std::env::var("GUARD_DOC_PORT").unwrap(), "8080");
+/// }
+/// assert_eq!(std::env::var("GUARD_DOC_HOST").unwrap(), "localhost");
+/// assert_eq!(std::env::var("GUARD_DOC_DEBUG").unwrap(), "1");
+/// assert!(std::env::var("GUARD_DOC_PORT").is_err());
+/// ```
+#[derive(Debug)]
+#[must_use = "the environment is restored when the guard is dropped"]
+pub struct EnvGuard {
+    vars: HashMap<OsString, OsString>,
+}
+
+impl EnvGuard {
+    /// Snapshot the process environment, use a [`Loader`](crate::Loader) or [`parse_and_set`](crate::parse_and_set) to change it
+    pub fn new() -> Self {
+        Self {
+            vars: std::env::vars_os().collect(),
+        }
+    }
+
+    /// Snapshot the process environment, then set the variables in an env string, see [`parse_and_set_with`](crate::parse_and_set_with)
+    pub fn from_env_str(data: &str) -> Self {
+        let guard = Self::new();
+        crate::parse_and_set_with(data, |entry| std::env::set_var(entry.key, &*entry.value));
+        guard
+    }
+}
+
+impl Default for EnvGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (key, _) in std::env::vars_os() {
+            if !self.vars.contains_key(&key) {
+                std::env::remove_var(key)
+            }
+        }
+        for (key, value) in &self.vars {
+            if std::env::var_os(key).as_ref() != Some(value) {
+                std::env::set_var(key, value)
+            }
+        }
+    }
+}
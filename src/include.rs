@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+
+use crate::{parse, path, LoadError};
+
+/// How deeply files can include each other, see [`Loader::includes`](crate::Loader::includes)
+pub(crate) const MAX_DEPTH: usize = 16;
+
+/// A file, or the part of a file between its include directives
+pub(crate) struct Segment {
+    pub(crate) path: PathBuf,
+    pub(crate) data: String,
+    /// How many lines of the file come before `data`
+    pub(crate) line: usize,
+    /// The `#if` sections open at the start of `data`
+    pub(crate) sections: Vec<bool>,
+    /// The files that included this one, ending with this one, empty if it isn't a file
+    chain: Vec<PathBuf>,
+}
+
+/// An include directive, and where the included file goes
+pub(crate) struct Include {
+    pub(crate) at: usize,
+    pub(crate) path: PathBuf,
+    chain: Vec<PathBuf>,
+}
+
+impl Segment {
+    pub(crate) fn new(path: PathBuf, data: String) -> Self {
+        Self {
+            chain: vec![path.clone()],
+            path,
+            data,
+            line: 0,
+            sections: vec![],
+        }
+    }
+
+    /// Env data that isn't a file, e.g. a fetched one, which can't include files
+    pub(crate) fn detached(name: &str, data: String) -> Self {
+        Self {
+            chain: vec![],
+            ..Self::new(PathBuf::from(name), data)
+        }
+    }
+}
+
+impl Include {
+    pub(crate) fn into_segment(self, data: String) -> Segment {
+        Segment {
+            path: self.path,
+            data,
+            line: 0,
+            sections: vec![],
+            chain: self.chain,
+        }
+    }
+}
+
+/// Finds the first `#include path` or `source path` in `sources[from..]`, splitting its source around it
+///
+/// The included path is relative to the including file.
+pub(crate) fn next(
+    sources: &mut Vec<Segment>,
+    from: usize,
+    options: parse::Options,
+) -> Result<Option<Include>, LoadError> {
+    for index in from..sources.len() {
+        let source = &sources[index];
+        if source.chain.is_empty() {
+            continue;
+        }
+        let (mut offset, mut line) = (0, source.line);
+        let mut sections = source.sections.clone();
+        let mut found = None;
+        for (chunk, _) in parse::chunks_in(&source.data, options, sections.clone()) {
+            let directive = match parse::section(chunk, &mut sections) || sections.contains(&false)
+            {
+                true => None,
+                false => directive(chunk),
+            };
+            if let Some(target) = directive {
+                found = Some((offset, offset + chunk.len(), line, target.to_string()));
+                break;
+            }
+            offset += chunk.len();
+            line += parse::line_breaks(chunk);
+        }
+
+        let (start, end, line, target) = match found {
+            Some(found) => found,
+            None => continue,
+        };
+
+        let dir = source.path.parent().unwrap_or(Path::new(""));
+        let path = dir.join(path::expand(Path::new(&target)));
+        let mut chain = source.chain.clone();
+        chain.push(path.clone());
+
+        let same = |a: &Path, b: &Path| match (a.canonicalize(), b.canonicalize()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => a == b,
+        };
+        if source.chain.iter().any(|p| same(p, &path)) {
+            return Err(LoadError::IncludeCycle { paths: chain });
+        }
+        if source.chain.len() > MAX_DEPTH {
+            return Err(LoadError::IncludeDepth { path });
+        }
+
+        let after = Segment {
+            path: source.path.clone(),
+            data: source.data[end..].to_string(),
+            line: line + parse::line_breaks(&source.data[start..end]),
+            sections,
+            chain: source.chain.clone(),
+        };
+        sources[index].data.truncate(start);
+        sources.insert(index + 1, after);
+        return Ok(Some(Include {
+            at: index + 1,
+            path,
+            chain,
+        }));
+    }
+    Ok(None)
+}
+
+/// The path of an include directive
+fn directive(chunk: &str) -> Option<&str> {
+    let line = chunk.trim();
+    let rest = line
+        .strip_prefix("#include")
+        .or_else(|| line.strip_prefix("source"))
+        .filter(|rest| rest.starts_with(char::is_whitespace))?
+        .trim();
+    let rest = ['\'', '"']
+        .into_iter()
+        .find_map(|q| rest.strip_prefix(q)?.strip_suffix(q))
+        .unwrap_or(rest);
+    Some(rest).filter(|rest| !rest.is_empty() && !rest.starts_with('='))
+}
+
+#[test]
+fn include_files() {
+    let dir = std::env::temp_dir().join("simple_env_load_include");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("shared")).unwrap();
+    std::fs::write(
+        dir.join("service.env"),
+        "SIMPLE_ENV_LOAD_INC_A=service\n#if os=none\n#include missing.env\n#else\n#include shared/base.env\nSIMPLE_ENV_LOAD_INC_C=service\n#else\nSIMPLE_ENV_LOAD_INC_C=other\n#endif",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("shared/base.env"),
+        "\nSIMPLE_ENV_LOAD_INC_B=base\nsource 'extra.env'",
+    )
+    .unwrap();
+    std::fs::write(dir.join("shared/extra.env"), "SIMPLE_ENV_LOAD_INC_C=extra").unwrap();
+
+    let report = crate::Loader::new()
+        .path(dir.join("service.env"))
+        .includes(true)
+        .load()
+        .unwrap();
+    let origins = report
+        .vars()
+        .iter()
+        .map(|var| (&*var.value, var.origin.to_string()))
+        .collect::<Vec<_>>();
+    let origin = |file: &str, line| format!("{}:{line}", dir.join(file).display());
+    assert_eq!(
+        origins,
+        [
+            ("service", origin("service.env", 1)),
+            ("base", origin("shared/base.env", 2)),
+            ("extra", origin("shared/extra.env", 1)),
+            ("service", origin("service.env", 6)),
+        ]
+    );
+
+    std::fs::write(dir.join("shared/extra.env"), "#include ../service.env").unwrap();
+    let err = crate::Loader::new()
+        .path(dir.join("service.env"))
+        .includes(true)
+        .load()
+        .unwrap_err();
+    assert!(matches!(err, LoadError::IncludeCycle { ref paths } if paths.len() == 4));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
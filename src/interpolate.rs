@@ -0,0 +1,32 @@
+use alloc::{string::String, vec::Vec};
+
+pub(crate) use simple_env_load_core::interpolate::*;
+
+use crate::parse::{Entry, Options};
+
+/// Expands the references in each value against the other pairs, then the process environment
+///
+/// See [`resolve`](simple_env_load_core::interpolate::resolve) for how references resolve.
+pub(crate) fn resolve(pairs: &[Entry<'_>]) -> Vec<Result<(String, String), ExpandError>> {
+    resolve_with(pairs, None, Options::default())
+}
+
+/// Like [`resolve`], running `$(command)`s with `run` if there is one
+pub(crate) fn resolve_with(
+    pairs: &[Entry<'_>],
+    run: Option<&Run>,
+    options: Options,
+) -> Vec<Result<(String, String), ExpandError>> {
+    simple_env_load_core::interpolate::resolve_with(pairs, run, options, &env)
+}
+
+/// A variable of the process, as [`store::get`](crate::store::get) sees it
+#[cfg(feature = "std")]
+fn env(key: &str) -> Option<String> {
+    crate::store::get(key)
+}
+
+#[cfg(not(feature = "std"))]
+fn env(_: &str) -> Option<String> {
+    None
+}
@@ -0,0 +1,35 @@
+/// The password stored in the platform keyring for `service/account`, see [`Loader::keyring`](crate::Loader::keyring)
+pub(crate) fn get(name: &str) -> Result<String, String> {
+    let (service, account) = name
+        .split_once('/')
+        .ok_or_else(|| format!("expected 'service/account', found '{name}'"))?;
+    ::keyring::Entry::new(service, account)
+        .and_then(|entry| entry.get_password())
+        .map_err(|err| err.to_string())
+}
+
+#[test]
+fn keyring_values() {
+    // the mock store doesn't keep passwords between entries, so every lookup fails
+    ::keyring::set_default_credential_builder(::keyring::mock::default_credential_builder());
+
+    let load = |data: &str| {
+        crate::Loader::new()
+            .source(data.to_string())
+            .keyring(true)
+            .load()
+    };
+    let report = load("SIMPLE_ENV_LOAD_KEYRING_QUOTED='@keyring:app/db'").unwrap();
+    assert_eq!(report.vars()[0].value, "@keyring:app/db");
+
+    let err = load("SIMPLE_ENV_LOAD_KEYRING=@keyring:app").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "cannot resolve 'SIMPLE_ENV_LOAD_KEYRING' at <string>:1: expected 'service/account', found 'app'"
+    );
+
+    let err = load("SIMPLE_ENV_LOAD_KEYRING=@keyring:app/db").unwrap_err();
+    assert!(
+        matches!(err, crate::LoadError::Value { ref key, .. } if key == "SIMPLE_ENV_LOAD_KEYRING")
+    );
+}
@@ -0,0 +1,116 @@
+use crate::write::json_string;
+
+/// A Kubernetes ConfigMap or Secret manifest with the variables as its data
+///
+/// Secrets have their values base64 encoded, as the `data` of a Secret must be.
+///
+/// ```rust
+/// use simple_env_load::Manifest;
+///
+/// let pairs = [("LOG_LEVEL", "info")];
+/// let yaml = Manifest::config_map("app-config").namespace("prod").to_yaml(pairs);
+/// assert_eq!(
+///     yaml,
+///     "\
+/// apiVersion: v1
+/// kind: ConfigMap
+/// metadata:
+///   name: \"app-config\"
+///   namespace: \"prod\"
+/// data:
+///   \"LOG_LEVEL\": \"info\"
+/// "
+/// );
+///
+/// let yaml = Manifest::secret("app-secrets").to_yaml([("DB_PASSWORD", "hunter2")]);
+/// assert!(yaml.contains("kind: Secret\n"));
+/// assert!(yaml.contains("type: Opaque\n"));
+/// assert!(yaml.ends_with("  \"DB_PASSWORD\": \"aHVudGVyMg==\"\n"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    secret: bool,
+    name: String,
+    namespace: Option<String>,
+}
+
+impl Manifest {
+    /// A ConfigMap named `name`
+    pub fn config_map(name: impl Into<String>) -> Self {
+        Self {
+            secret: false,
+            name: name.into(),
+            namespace: None,
+        }
+    }
+
+    /// An `Opaque` Secret named `name`
+    pub fn secret(name: impl Into<String>) -> Self {
+        Self {
+            secret: true,
+            ..Self::config_map(name)
+        }
+    }
+
+    /// The namespace of the object, it's left out of the manifest by default
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// The manifest as YAML, with the `(key, value)` pairs as its data
+    ///
+    /// When a key is in `pairs` more than once, the last one is used.
+    pub fn to_yaml<I, K, V>(&self, pairs: I) -> String
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut data = Vec::<(String, String)>::new();
+        for (key, value) in pairs {
+            let (key, value) = (key.as_ref(), value.as_ref());
+            data.retain(|(k, _)| k != key);
+            data.push((key.to_string(), value.to_string()));
+        }
+
+        let kind = if self.secret { "Secret" } else { "ConfigMap" };
+        let mut out = format!("apiVersion: v1\nkind: {kind}\nmetadata:\n");
+        out.push_str(&format!("  name: {}\n", json_string(&self.name)));
+        if let Some(namespace) = &self.namespace {
+            out.push_str(&format!("  namespace: {}\n", json_string(namespace)));
+        }
+        if self.secret {
+            out.push_str("type: Opaque\n");
+        }
+
+        out.push_str(if data.is_empty() {
+            "data: {}\n"
+        } else {
+            "data:\n"
+        });
+        for (key, value) in data {
+            let value = match self.secret {
+                true => crate::base64::encode(value.as_bytes()),
+                false => value,
+            };
+            out.push_str(&format!(
+                "  {}: {}\n",
+                json_string(&key),
+                json_string(&value)
+            ));
+        }
+        out
+    }
+}
+
+#[test]
+fn kube_manifests() {
+    let pairs = [("A", "1"), ("B", "line 1\nline \"2\""), ("A", "2")];
+    let yaml = Manifest::config_map("cfg").to_yaml(pairs);
+    assert!(yaml.ends_with("data:\n  \"B\": \"line 1\\nline \\\"2\\\"\"\n  \"A\": \"2\"\n"));
+    assert!(!yaml.contains("namespace"));
+
+    let yaml = Manifest::secret("s").to_yaml([("", ""); 0]);
+    assert!(yaml.ends_with("type: Opaque\ndata: {}\n"));
+}
@@ -22,29 +22,506 @@ will produce:
 `TEST_DATA`|`bar`
 `TEST_BAZ`|`baz`
 `TEST_BAR`|`bar`
+
+A `#` in an unquoted value starts a comment wherever it is, like Ruby's dotenv. `Loader::comment_policy` can require whitespace
+before it instead, so `PASS=abc#123` keeps its `#`, which is what `Dialect::Compose` does for files written for Docker Compose.
+Unquoted values are trimmed, and quoted values keep their whitespace exactly, e.g. `KEY="  padded  "`,
+unless `Loader::trim_values` says otherwise.
+
+# Shell files
+A leading `export` is ignored, as is fish's `set -x`/`set -gx`, so shell rc-style files can be loaded as is.
+That includes direnv's `.envrc` files, their lines that aren't assignments (e.g. `dotenv` or `PATH_add bin`) are skipped,
+and `Shell::Direnv` writes variables in the JSON format of `direnv dump json`.
+```text
+export TEST_DATA=bar
+set -x TEST_BAZ baz
+```
+
+# Empty and unset values
+`KEY=` sets `KEY` to an empty value. With `Loader::unset_directives`, `unset KEY` or a bare `KEY` removes it instead,
+so a more specific file can delete a variable an earlier one defined.
+```text
+DEBUG=
+unset LOG_FILE
+```
+
+# Multiline values
+Quoted values can span several lines, the line breaks are kept as is unless `Loader::normalize_newlines` is set.
+Lines end with `\n`, `\r\n`, a lone `\r`, or a Unicode line or paragraph separator.
+```text
+CERT="-----BEGIN CERTIFICATE-----
+MIIB...
+-----END CERTIFICATE-----"
+```
+Long values can also be written as a heredoc, the lines up to one with just its delimiter are the value, without escapes.
+They're expanded like unquoted values, unless the delimiter is quoted, e.g. `KEY<<'EOF'`.
+This is the format of GitHub Actions' `$GITHUB_ENV` file.
+```text
+FEATURES<<EOF
+{"beta": true, "home": "$HOME"}
+EOF
+```
+
+# Escapes
+Double quoted values understand `\n`, `\t`, `\r`, `\\`, `\"`, `\$` and `\uXXXX`,
+single quoted values are taken literally.
+Quoted segments that aren't separated by whitespace are joined, like in a shell,
+a `\` between them escapes the next character, and only a comment can follow a quoted value.
+```text
+GREETING="hello\tworld\n"     # a tab and a trailing newline
+RAW='hello\tworld\n'          # backslashes are kept
+JOINED='it'\''s'              # it's
+QUOTES="she said \"hi\""      # she said "hi"
+```
+
+# Interpolation
+Unquoted and double quoted values can reference other variables with `$VAR` or `${VAR}`.
+These resolve against the other loaded keys first, then the process environment.
+Single quoted values are never expanded, and `\$` is a literal `$`.
+With `Loader::percent_references`, the `%VAR%` references of Windows batch files are expanded too.
+```text
+DB_HOST=localhost
+DATABASE_URL=postgres://$DB_HOST/${DB_NAME}
+DB_NAME=app
+PATH=$PATH:/opt/app/bin     # a key referencing itself uses its previous definition, or the process environment
+```
+
+Like a POSIX shell, `${VAR:-default}` is used when `VAR` is unset or empty, and `${VAR:?message}` fails
+when it is. Without the `:`, only unset variables count.
+```text
+LOG_LEVEL=${LOG_LEVEL:-info}
+API_KEY=${API_KEY:?the api key must be set}
+```
+
+`render` fills the `${VAR}` placeholders of other files, such as an `nginx.conf.tmpl`, with the same rules.
+
+# Conditional sections
+Lines between `#if` and `#endif` are only used when the condition holds for the current platform,
+`#else` starts the lines used when it doesn't. Conditions compare `os`, `family` or `arch`
+(as in `std::env::consts`) with `=` or `!=` to one or more comma separated values.
+```text
+#if family=unix
+SOCKET=/var/run/app.sock
+#else
+SOCKET=\\.\pipe\app
+#endif
+```
+
+# no_std
+Without the default `std` feature the crate is `no_std` (it needs `alloc`), leaving only the parser:
+`parse_iter`, `parse_diagnostics`, `parse_to_map`, `parse_and_set`, `parse_and_set_with` and `parse_bytes_with`,
+with every `Dialect`.
+The rules are the same, except that references to keys that aren't in the data are unset,
+as there's no process environment to fall back on.
+```toml
+simple_env_load = { version = "0.2", default-features = false }
+```
+
+# Features
+|Feature|Adds|
+|---|---|
+`std`|(default) reading files and the process environment, everything but the parser
+`tokio`|async loading with `tokio::fs`, see `Loader::load_async`
+`serde`|deserializing env data into a struct, see `from_str`
+`derive`|`#[derive(EnvLoad)]` for typed env structs, see `EnvLoad`, and `load_env!` to embed an env file at compile time
+`http`|fetching env files over HTTP(S), see `load_env_from_url` and `Remote`
+`aws`|reading AWS SSM parameters and Secrets Manager secrets with the `aws` CLI, see `SsmParameters` and `SecretsManagerSecret`
+`vault`|reading HashiCorp Vault KV secrets, see `VaultSecret`
+`age`|decrypting `.env.enc` files encrypted with age, see `Loader::age_identity`
+`sops`|decrypting SOPS encrypted dotenv files with the `sops` binary, see `Loader::sops`
+`keyring`|`@keyring:service/account` values read from the platform keyring, see `Loader::keyring`
+`watch`|watching env files for changes, see `Loader::watch`
+`signal`|reloading env files on `SIGHUP` on Unix, see `Loader::reload_on_sighup`
+`cli`|the `senv` binary, `senv run -- <command>` runs a command with the env files loaded, `senv export` prints them as a shell script, `senv check` lints them (with `--secrets`, for credentials too), `senv diff` compares two of them and `senv docs` documents them
+`regex`|regular expression constraints for a `Schema`, see `Rule::pattern`
+`url`|values parsed as a `url::Url`, see `Env::get_url`
+`json`|loading `.json` files of variables, see `Loader::path`
+`serde_json`|values parsed as JSON, see `Env::get_json`
+`yaml`|loading `.yaml` and `.yml` files of variables, see `Loader::path`
+`toml`|loading `.toml` files of variables, or a table of them, see `Loader::toml_table`
+`figment`|a figment `Provider` of the variables in env files, see `EnvProvider`
+`config`|a config-rs `Source` of the variables in env files, see `EnvProvider`
+`clap`|default values for clap arguments from env files, see `clap_defaults`
+`mmap`|parsing large env files without reading them into memory, see `MappedFile`
+`tracing`|`tracing` events for the files a `Loader` reads and the keys it sets, never their values
 */
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+
+/// Emits a `tracing` event with the `tracing` feature, without it the arguments aren't evaluated
+#[cfg(feature = "std")]
+macro_rules! trace_event {
+    ($level:ident, $($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($args)*)
+    };
+}
+
+#[cfg(feature = "age")]
+mod age;
+
+#[cfg(feature = "clap")]
+mod args;
+#[cfg(feature = "clap")]
+pub use args::clap_defaults;
+
+#[cfg(feature = "aws")]
+mod aws;
+#[cfg(feature = "aws")]
+pub use aws::{SecretsManagerSecret, SsmParameters};
+
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "serde")]
+pub use de::{from_str, DeserializeError};
+
+#[cfg(feature = "std")]
+mod annotation;
+#[cfg(feature = "std")]
+pub use annotation::{annotations, generate_docs, AnnotatedKey, AnnotationError, DocFormat};
+
+#[cfg(feature = "std")]
+mod base64;
+
+#[cfg(feature = "std")]
+pub mod build;
+
+#[cfg(feature = "std")]
+mod command;
+
+pub use simple_env_load_core::dialect::Dialect;
+use simple_env_load_core::parse;
+
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+pub use diff::{diff, ChangedValue, EnvDiff};
+
+#[cfg(feature = "std")]
+mod document;
+#[cfg(feature = "std")]
+pub use document::EnvDocument;
+
+#[cfg(feature = "std")]
+mod encoding;
+
+#[cfg(feature = "std")]
+mod env;
+#[cfg(feature = "std")]
+pub use env::{Env, TrackedEnv};
+
+#[cfg(feature = "std")]
+mod env_load;
+#[cfg(feature = "std")]
+pub use env_load::{EnvLoad, EnvLoadError};
+#[cfg(feature = "derive")]
+pub use simple_env_load_derive::{load_env, EnvLoad};
+
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub use env_load::__private;
+
+// lets the code generated by the derive macro be used in this crate's tests
+#[cfg(all(test, feature = "derive"))]
+extern crate self as simple_env_load;
+
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+pub use error::LoadError;
+
+#[cfg(feature = "std")]
+mod find;
+#[cfg(feature = "std")]
+pub use find::{find, find_and_load, find_and_load_from};
+
+#[cfg(feature = "std")]
+mod glob;
+
+#[cfg(feature = "std")]
+mod guard;
+#[cfg(feature = "std")]
+pub use guard::EnvGuard;
+
+#[cfg(feature = "std")]
+mod include;
+
+#[cfg(feature = "keyring")]
+mod keyring;
+
+#[cfg(feature = "std")]
+mod kube;
+#[cfg(feature = "std")]
+pub use kube::Manifest;
+
+mod interpolate;
+pub use simple_env_load_core::interpolate::ExpandError;
+
+#[cfg(feature = "std")]
+mod limits;
+#[cfg(feature = "std")]
+pub use limits::Limits;
+
+#[cfg(feature = "std")]
+mod lint;
+#[cfg(feature = "std")]
+pub use lint::{lint, lint_with_example, verify_against_example, ExampleDiff, Lint, LintKind};
+
+#[cfg(feature = "std")]
+mod loader;
+#[cfg(feature = "std")]
+pub use loader::{Backend, DuplicatePolicy, KeyPolicy, Loader};
+
+#[cfg(feature = "std")]
+mod merge;
+#[cfg(feature = "std")]
+pub use merge::{merge, Conflict, Decision, MergeStrategy, Merged};
+
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "mmap")]
+pub use mmap::MappedFile;
+
+#[cfg(feature = "std")]
+mod path;
+
+#[cfg(any(feature = "figment", feature = "config"))]
+mod provider;
+#[cfg(any(feature = "figment", feature = "config"))]
+pub use provider::EnvProvider;
+
+#[cfg(feature = "http")]
+mod remote;
+#[cfg(feature = "http")]
+pub use remote::Remote;
+
+#[cfg(feature = "std")]
+mod require;
+#[cfg(feature = "std")]
+pub use require::{require, MissingKeys};
+
+pub use simple_env_load_core::parse::{CommentPolicy, Diagnostic, Entry, Reason, TrimPolicy};
+
+#[cfg(feature = "std")]
+mod schema;
+#[cfg(feature = "std")]
+pub use schema::{Rule, Schema, SchemaError, Type, Violation};
+
+#[cfg(feature = "std")]
+mod secret_scan;
+#[cfg(feature = "std")]
+pub use secret_scan::{lint_secrets, SecretKind};
+
+#[cfg(feature = "std")]
+mod secrets_dir;
+#[cfg(feature = "std")]
+pub use secrets_dir::SecretsDir;
+
+#[cfg(feature = "sops")]
+mod sops;
+
+#[cfg(all(unix, feature = "signal"))]
+mod signal;
+
+#[cfg(feature = "std")]
+pub mod store;
+
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+pub use stream::parse_reader;
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+mod structured;
+
+#[cfg(feature = "std")]
+mod source;
+#[cfg(feature = "std")]
+pub use source::{Reader, Source, SourceError};
+
+#[cfg(feature = "std")]
+mod template;
+#[cfg(feature = "std")]
+pub use template::{render, render_with, UnsetPolicy};
+
+#[cfg(feature = "std")]
+mod tree;
+#[cfg(feature = "std")]
+pub use tree::Tree;
+
+#[cfg(feature = "std")]
+mod redact;
+#[cfg(feature = "std")]
+pub use redact::{Redacted, RedactedReport};
+
+#[cfg(feature = "std")]
+mod report;
+#[cfg(feature = "std")]
+pub use report::{Changes, Duplicate, InvalidKey, LoadReport, LoadStats, LoadedVar, Origin};
+
+#[cfg(feature = "vault")]
+mod vault;
+#[cfg(feature = "vault")]
+pub use vault::VaultSecret;
+
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "watch")]
+pub use watch::Watcher;
+
+#[cfg(feature = "std")]
+mod vfs;
+#[cfg(feature = "std")]
+pub use vfs::Vfs;
+
+#[cfg(feature = "std")]
+mod write;
+#[cfg(feature = "std")]
+pub use write::{
+    append_github_env, to_env_string, to_env_string_as, to_shell_string, write_env, write_env_as,
+    write_file_atomic, write_github_env, write_shell, Shell,
+};
 
 /// Tries to load the env. vars from these paths
 ///
+/// A leading `~` in a path is the home directory, and `$VAR`/`${VAR}` are taken from the process environment.
+///
 /// ```rust
 /// // this will add envs it finds from the first to the last
 /// // so important (read: secret/user) ends should be at the end of the iterator
 /// simple_env_load::load_env_from(&["./env", "~/.config/.env"]);
 /// ```
+#[cfg(feature = "std")]
 pub fn load_env_from<I, T>(paths: I)
 where
     I: IntoIterator<Item = T>,
     T: AsRef<std::path::Path>,
 {
-    paths
+    let files = paths
+        .into_iter()
+        .flat_map(|path| std::fs::read_to_string(path::expand(path.as_ref())))
+        .collect::<Vec<_>>();
+
+    set_all(&files)
+}
+
+/// Tries to load the env. vars from these paths without blocking the async runtime
+///
+/// This behaves like [`load_env_from`], the files are read with [`tokio::fs`].
+///
+/// ```rust
+/// # async fn run() {
+/// simple_env_load::load_env_from_async(&["./env", "./env.local"]).await;
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn load_env_from_async<I, T>(paths: I)
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<std::path::Path>,
+{
+    let mut files = vec![];
+    for path in paths {
+        if let Ok(data) = tokio::fs::read_to_string(path::expand(path.as_ref())).await {
+            files.push(data)
+        }
+    }
+
+    set_all(&files)
+}
+
+#[cfg(feature = "std")]
+fn set_all(files: &[String]) {
+    let pairs = files
+        .iter()
+        .flat_map(|data| parse::parse(data, Default::default()))
+        .collect::<Vec<_>>();
+
+    interpolate::resolve(&pairs)
         .into_iter()
-        .map(std::fs::read_to_string)
         .flatten()
-        .for_each(|data| parse_and_set(&data, |k, v| std::env::set_var(&k, &v)))
+        .for_each(|(k, v)| std::env::set_var(k, v))
+}
+
+/// Tries to load the env. vars from these paths, failing on the first file that couldn't be read
+///
+/// Unlike [`load_env_from`], nothing is set unless every file could be read.
+/// This is a shorthand for [`Loader::load`], which has more options.
+/// Keys in [`Loader::DEFAULT_DENY_LIST`], such as `PATH` and `LD_PRELOAD`, are never set.
+/// On success, the pairs that were set are returned in the order they were applied.
+///
+/// ```rust
+/// let err = simple_env_load::try_load_env_from(&["./this/does/not/exist.env"]).unwrap_err();
+/// assert_eq!(err.kind(), Some(std::io::ErrorKind::NotFound));
+/// assert_eq!(err.path(), Some(std::path::Path::new("./this/does/not/exist.env")));
+/// ```
+#[cfg(feature = "std")]
+pub fn try_load_env_from<I, T>(paths: I) -> Result<Vec<(String, String)>, LoadError>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<std::path::Path>,
+{
+    Loader::new()
+        .paths(paths)
+        .load()
+        .map(LoadReport::into_pairs)
+}
+
+/// Loads the conventional files for a profile from the current directory, skipping the ones that don't exist
+///
+/// In order of precedence, from lowest to highest:
+/// `.env`, `.env.local`, `.env.{profile}`, `.env.{profile}.local`.
+/// This is a shorthand for [`Loader::profile`].
+///
+/// ```rust
+/// simple_env_load::load_profile("production").unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn load_profile(profile: &str) -> Result<LoadReport, LoadError> {
+    Loader::new().profile(profile).load()
+}
+
+/// Reads env data from standard input until it's closed, then sets the variables it defines
+///
+/// This is a shorthand for a [`Loader::path`] of `-`, so pipelines work without temporary files,
+/// e.g. `vault kv get -format=dotenv secret/app | app`.
+///
+/// ```rust,no_run
+/// simple_env_load::load_env_from_stdin().unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn load_env_from_stdin() -> Result<LoadReport, LoadError> {
+    Loader::new().path("-").load()
+}
+
+/// Fetches an env file over HTTP(S), then sets the variables it defines
+///
+/// This is a shorthand for [`Loader::url`], use a [`Remote`] for auth, timeouts and retries.
+///
+/// ```rust,no_run
+/// use simple_env_load::Remote;
+///
+/// simple_env_load::load_env_from_url("https://config.example.com/app.env").unwrap();
+///
+/// let token = std::env::var("CONFIG_TOKEN").unwrap();
+/// simple_env_load::load_env_from_url(
+///     Remote::new("https://config.example.com/secrets.env").bearer_token(token),
+/// )
+/// .unwrap();
+/// ```
+#[cfg(feature = "http")]
+pub fn load_env_from_url(url: impl Into<Remote>) -> Result<LoadReport, LoadError> {
+    Loader::new().url(url).load()
 }
 
 /// Parse an env string and calls a function for each key=value pair
 ///
+/// Values are expanded as described in the [crate docs](crate#interpolation),
+/// pairs that reference each other in a cycle are skipped.
+///
 /// This is useful for mocking and testing
 ///
 /// ```rust
@@ -70,88 +547,173 @@ where
 /// assert_eq!(std::env::var("TEST_BAR").unwrap(), "\"nested\"");
 /// ```
 pub fn parse_and_set(data: &str, set: fn(k: &str, v: &str)) {
-    parse(data).for_each(|(k, v)| set(k, v))
+    let pairs = parse::parse(data, Default::default()).collect::<Vec<_>>();
+    interpolate::resolve(&pairs)
+        .into_iter()
+        .flatten()
+        .for_each(|(k, v)| set(&k, &v))
 }
 
-fn parse(data: &str) -> impl Iterator<Item = (&str, &str)> + '_ {
-    data.lines().map(<str>::trim).filter_map(|s| {
-        if s.starts_with('#') {
-            return None;
+/// Parse an env string and calls a function with each entry, including where it was and how it was written
+///
+/// Like [`parse_and_set`], values are expanded and pairs that reference each other in a cycle are skipped.
+/// [`Entry::raw`] is the value as it was written.
+///
+/// ```rust
+/// let data = "HOST=localhost\nURL = \"http://$HOST\" # the url";
+/// let mut entries = vec![];
+/// simple_env_load::parse_and_set_with(data, |entry| entries.push(entry));
+///
+/// let url = &entries[1];
+/// assert_eq!((url.key, &*url.value, url.raw()), ("URL", "http://localhost", "http://$HOST"));
+/// assert_eq!((url.line, url.is_quoted()), (2, true));
+/// assert_eq!(&data[url.span.clone()], "URL = \"http://$HOST\" # the url");
+/// ```
+pub fn parse_and_set_with<'a>(data: &'a str, mut set: impl FnMut(Entry<'a>)) {
+    let pairs = parse::parse(data, Default::default()).collect::<Vec<_>>();
+    let values = interpolate::resolve(&pairs);
+    for (entry, value) in pairs.into_iter().zip(values) {
+        if let Ok((_, value)) = value {
+            set(Entry {
+                value: value.into(),
+                ..entry
+            })
         }
-
-        let mut iter = s.splitn(2, '=').map(<str>::trim).map(parse_str);
-        let (head, tail) = (iter.next()??, iter.next()??);
-        Some((head, tail))
-    })
-}
-
-fn parse_str(input: &str) -> Option<&str> {
-    if !input.contains(|c| matches!(c, '"' | '\'')) {
-        return input.splitn(2, '#').map(<str>::trim).next();
-    }
-
-    #[derive(Debug)]
-    enum Flavor {
-        Single,
-        Double,
-        Unknown,
     }
+}
 
-    let mut flavor = Flavor::Unknown;
-    let (mut start, mut end) = (None, None);
-
-    for (i, c) in input.char_indices() {
-        if start.is_some() && end.is_some() {
-            break;
-        }
-
-        if matches!(flavor, Flavor::Unknown) {
-            flavor = match c {
-                '\'' => Flavor::Single,
-                '"' => Flavor::Double,
-                _ => continue,
-            };
-        }
-
-        if match flavor {
-            Flavor::Single => '\'',
-            Flavor::Double => '"',
-            Flavor::Unknown => unreachable!(),
-        } != c
-        {
-            continue;
+/// Parse env data that might not be UTF-8, calling a function with each entry
+///
+/// Invalid UTF-8 is replaced with `U+FFFD`, like [`String::from_utf8_lossy`], and the 1-based lines it was on
+/// are returned so they can be reported. Otherwise this behaves like [`parse_and_set_with`],
+/// with spans into the replaced data.
+///
+/// ```rust
+/// let data = b"NAME=caf\xe9\nHOST=localhost\nPATH_LIST=a:\xff:\xfe";
+/// let mut entries = vec![];
+/// let lines = simple_env_load::parse_bytes_with(data, |entry| {
+///     entries.push((entry.key.to_string(), entry.value.into_owned()))
+/// });
+///
+/// assert_eq!(lines, [1, 3]);
+/// assert_eq!(entries[0], ("NAME".into(), "caf\u{fffd}".into()));
+/// assert_eq!(entries[1], ("HOST".into(), "localhost".into()));
+/// ```
+pub fn parse_bytes_with(data: &[u8], set: impl FnMut(Entry<'_>)) -> Vec<usize> {
+    let (mut lines, mut line) = (vec![], 1);
+    for chunk in data.utf8_chunks() {
+        line += parse::line_breaks(chunk.valid());
+        if !chunk.invalid().is_empty() && lines.last() != Some(&line) {
+            lines.push(line)
         }
+    }
+    parse_and_set_with(&String::from_utf8_lossy(data), set);
+    lines
+}
 
-        match (start, end) {
-            (None, ..) => {
-                start.get_or_insert(i + 1);
-            }
-            (Some(_), None) => {
-                end.get_or_insert(i - 1);
+/// Parse an env string into its `(key, value)` pairs, without touching the process environment
+///
+/// Each key appears once, in the order it was first defined, with the value it was last defined as.
+/// Values are expanded as described in the [crate docs](crate#interpolation).
+///
+/// ```rust
+/// let data = "HOST=localhost\nPORT=80\nURL=http://$HOST:$PORT\nPORT=8080";
+/// let map = simple_env_load::parse_to_map(data);
+/// assert_eq!(
+///     map,
+///     [
+///         ("HOST".to_string(), "localhost".to_string()),
+///         ("PORT".to_string(), "8080".to_string()),
+///         ("URL".to_string(), "http://localhost:8080".to_string()),
+///     ]
+/// );
+/// ```
+pub fn parse_to_map(data: &str) -> Vec<(String, String)> {
+    let pairs = parse::parse(data, Default::default()).collect::<Vec<_>>();
+    let mut seen = BTreeMap::<String, usize>::new();
+    let mut map = Vec::<(String, String)>::new();
+    for (k, v) in interpolate::resolve(&pairs).into_iter().flatten() {
+        match seen.get(&k) {
+            Some(&i) => map[i].1 = v,
+            None => {
+                seen.insert(k.clone(), map.len());
+                map.push((k, v));
             }
-            _ => {}
-        };
+        }
     }
+    map
+}
 
-    let (start, end) = (start?, end?);
-    input.get(start..start + end)
+/// Lazily parse an env string, borrowing from it
+///
+/// Keys always borrow from `data`, and so do values unless they had to be rewritten:
+/// a double quoted value containing escapes is the only case that allocates.
+/// Values are returned as written, without [interpolation](crate#interpolation),
+/// and lines that can't be parsed are skipped (see [`parse_diagnostics`]).
+///
+/// ```rust
+/// use std::borrow::Cow;
+///
+/// let data = "HOST=localhost\nGREETING=\"hello\\tworld\"";
+/// let mut iter = simple_env_load::parse_iter(data);
+///
+/// let host = iter.next().unwrap();
+/// assert_eq!((host.key, host.line), ("HOST", 1));
+/// assert!(matches!(host.value, Cow::Borrowed("localhost")));
+///
+/// let greeting = iter.next().unwrap();
+/// assert!(matches!(greeting.value, Cow::Owned(..)));
+/// assert_eq!(greeting.value, "hello\tworld");
+/// ```
+pub fn parse_iter(data: &str) -> impl Iterator<Item = Entry<'_>> + '_ {
+    parse::parse(data, Default::default())
 }
 
-#[test]
-fn parse_octos_in_strings() {
-    macro_rules! val {
-        ($k:expr => $v:expr) => {
-            &[($k, $v)]
-        };
+/// Parse an env string, reporting the lines that couldn't be parsed instead of skipping them
+///
+/// Values are returned as written, without [interpolation](crate#interpolation).
+///
+/// ```rust
+/// use simple_env_load::Reason;
+///
+/// let data = "HOST=localhost\nPORT 8080\nNAME=\"unterminated";
+/// let (entries, diagnostics) = simple_env_load::parse_diagnostics(data);
+///
+/// assert_eq!(entries.len(), 1);
+/// assert_eq!(entries[0].key, "HOST");
+///
+/// assert_eq!(diagnostics[0].line, 2);
+/// assert_eq!(diagnostics[0].reason, Reason::MissingEquals);
+/// assert_eq!(diagnostics[1].line, 3);
+/// assert_eq!(diagnostics[1].column, 6);
+/// assert_eq!(diagnostics[1].reason, Reason::UnterminatedQuote);
+/// ```
+pub fn parse_diagnostics(data: &str) -> (Vec<Entry<'_>>, Vec<Diagnostic>) {
+    let (mut entries, mut diagnostics) = (vec![], vec![]);
+    for line in parse::parse_lines(data, Default::default()) {
+        match line {
+            Ok(entry) => entries.push(entry),
+            Err(diagnostic) => diagnostics.push(diagnostic),
+        }
     }
+    (entries, diagnostics)
+}
 
-    #[rustfmt::skip]
-    let tests: &[(&str, &[(&str, &str)])] = &[
-        (r##"FOO="#bar""##, val!("FOO"  => "#bar")),
-        (r"'asdf'='fdsa'",  val!("asdf" => "fdsa")),
-        (r##"#FOO="bar""##, &[]),
-    ];
-    for (input, expected) in tests {
-        assert_eq!(parse(input).collect::<Vec<_>>(), *expected);
+/// Sets the env. var only if it isn't already set in the process environment
+///
+/// This can be passed to [`parse_and_set`] so that the existing environment wins,
+/// see [`Loader::override_existing`] for the same behavior when loading files.
+///
+/// ```rust
+/// std::env::set_var("TEST_ABSENT_EXISTING", "from_process");
+/// let data = "TEST_ABSENT_EXISTING=from_data\nTEST_ABSENT_NEW=from_data";
+/// simple_env_load::parse_and_set(data, simple_env_load::set_if_absent);
+/// assert_eq!(std::env::var("TEST_ABSENT_EXISTING").unwrap(), "from_process");
+/// assert_eq!(std::env::var("TEST_ABSENT_NEW").unwrap(), "from_data");
+/// ```
+#[cfg(feature = "std")]
+pub fn set_if_absent(key: &str, value: &str) {
+    if std::env::var_os(key).is_none() {
+        std::env::set_var(key, value)
     }
 }
@@ -0,0 +1,142 @@
+use std::io::Read;
+
+/// The most an env file can hold, to load files from untrusted sources, see [`Loader::limits`](crate::Loader::limits)
+///
+/// Every limit is in bytes, apart from [`max_entries`](Self::max_entries), and none of them are set by default.
+///
+/// ```rust
+/// use simple_env_load::{Limits, Loader};
+///
+/// let limits = Limits::new()
+///     .max_file_size(64 * 1024)
+///     .max_line_length(4096)
+///     .max_value_length(1024)
+///     .max_entries(100);
+///
+/// let data = format!("LIMITS_DOC_HOST=localhost\nLIMITS_DOC_KEY={}", "x".repeat(2000));
+/// let err = Loader::new().source(data).limits(limits).load().unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "'<string>' exceeds a limit: the value of 'LIMITS_DOC_KEY' at line 2 is longer than 1024 bytes"
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    file_size: Option<usize>,
+    line_length: Option<usize>,
+    value_length: Option<usize>,
+    entries: Option<usize>,
+}
+
+impl Limits {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The largest a file can be, files are read no further than this
+    ///
+    /// This applies to standard input, remote files and [`Source`](crate::Source)s too. A custom `Source` or
+    /// [`Vfs`](crate::Vfs) only stops early if it implements `read_limited`.
+    pub fn max_file_size(mut self, bytes: usize) -> Self {
+        self.file_size = Some(bytes);
+        self
+    }
+
+    /// The longest a line can be, including comments
+    pub fn max_line_length(mut self, bytes: usize) -> Self {
+        self.line_length = Some(bytes);
+        self
+    }
+
+    /// The longest a value can be, both as it's written and once it's expanded
+    pub fn max_value_length(mut self, bytes: usize) -> Self {
+        self.value_length = Some(bytes);
+        self
+    }
+
+    /// The most entries every file together can define, unsets included
+    pub fn max_entries(mut self, entries: usize) -> Self {
+        self.entries = Some(entries);
+        self
+    }
+
+    /// How much of a file to read, one byte more than the limit so a larger file is noticed
+    pub(crate) fn read_size(&self) -> Option<u64> {
+        self.file_size.map(|max| max as u64 + 1)
+    }
+
+    pub(crate) fn check_file(&self, size: usize) -> Result<(), String> {
+        match self.file_size {
+            Some(max) if size > max => Err(format!("it's larger than {max} bytes")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks every line of `data`, its first line being `line`, lines end where the parser ends them
+    pub(crate) fn check_lines(&self, data: &str, mut line: usize) -> Result<(), String> {
+        let Some(max) = self.line_length else {
+            return Ok(());
+        };
+        let mut rest = data;
+        while !rest.is_empty() {
+            let (text, next) = crate::parse::split_line(rest);
+            if text.len() > max {
+                return Err(format!("line {line} is longer than {max} bytes"));
+            }
+            (rest, line) = (next, line + 1);
+        }
+        Ok(())
+    }
+
+    /// The longest a value can be, both as it's written and once it's expanded
+    pub(crate) fn value_length(&self) -> Option<usize> {
+        self.value_length
+    }
+
+    pub(crate) fn check_value(&self, key: &str, value: &str, line: usize) -> Result<(), String> {
+        match self.value_length {
+            Some(max) if value.len() > max => Err(too_long(key, line, max)),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_entries(&self, entries: usize) -> Result<(), String> {
+        match self.entries {
+            Some(max) if entries > max => Err(format!("there are more than {max} entries")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Why the value of `key` exceeds [`Limits::max_value_length`]
+pub(crate) fn too_long(key: &str, line: usize, max: usize) -> String {
+    format!("the value of '{key}' at line {line} is longer than {max} bytes")
+}
+
+/// Reads `reader` to its end, but no more than `limit` bytes of it
+pub(crate) fn read_limited(reader: impl Read, limit: Option<u64>) -> std::io::Result<Vec<u8>> {
+    let mut data = vec![];
+    reader
+        .take(limit.unwrap_or(u64::MAX))
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Like [`read_limited`], as text
+///
+/// Text that was cut short can end in the middle of a character, which is replaced rather than removed,
+/// so it's still larger than the limit.
+pub(crate) fn read_to_string_limited(
+    reader: impl Read,
+    limit: Option<u64>,
+) -> std::io::Result<String> {
+    let data = read_limited(reader, limit)?;
+    match String::from_utf8(data) {
+        Ok(data) => Ok(data),
+        Err(err) if limit == Some(err.as_bytes().len() as u64) => {
+            Ok(String::from_utf8_lossy(err.as_bytes()).into_owned())
+        }
+        Err(err) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err)),
+    }
+}
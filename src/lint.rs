@@ -0,0 +1,299 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{parse, LoadError, Reason};
+
+/// A problem [`lint`] found in an env file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// The 1-based line of the problem, in the example for [`LintKind::Missing`]
+    pub line: usize,
+    /// What the problem is
+    pub kind: LintKind,
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.line, self.kind)
+    }
+}
+
+/// The kinds of problems [`lint`] finds
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LintKind {
+    /// The line couldn't be parsed, such as an unterminated quote
+    Malformed(Reason),
+    /// The key was already defined on the `first` line
+    Duplicate { key: String, first: usize },
+    /// The key differs only by case from the `other` key, defined on the `first` line
+    ///
+    /// These are the same variable on Windows, see [`Loader::case_insensitive`](crate::Loader::case_insensitive).
+    CaseCollision {
+        key: String,
+        other: String,
+        first: usize,
+    },
+    /// An unquoted value ends with whitespace, which is dropped
+    TrailingWhitespace { key: String },
+    /// The key isn't uppercase letters, digits and underscores, not starting with a digit
+    ///
+    /// A quoted key is reported with its quotes.
+    InvalidKey { key: String },
+    /// The value looks like a credential, see [`lint_secrets`](crate::lint_secrets)
+    PossibleSecret {
+        key: String,
+        kind: crate::SecretKind,
+    },
+    /// The key is in the file, but not in the example
+    Undocumented { key: String },
+    /// The key is in the example, but not in the file
+    Missing { key: String },
+}
+
+impl std::fmt::Display for LintKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(reason) => write!(f, "{reason}"),
+            Self::Duplicate { key, first } => {
+                write!(f, "'{key}' was already defined on line {first}")
+            }
+            Self::CaseCollision { key, other, first } => {
+                write!(
+                    f,
+                    "'{key}' differs only by case from '{other}' on line {first}"
+                )
+            }
+            Self::TrailingWhitespace { key } => {
+                write!(f, "the value of '{key}' has trailing whitespace")
+            }
+            Self::InvalidKey { key } => {
+                write!(f, "'{key}' should match [A-Z_][A-Z0-9_]*")
+            }
+            Self::PossibleSecret { key, kind } => {
+                write!(f, "the value of '{key}' looks like {kind}")
+            }
+            Self::Undocumented { key } => write!(f, "'{key}' isn't in the example"),
+            Self::Missing { key } => write!(f, "'{key}' is in the example, but isn't defined"),
+        }
+    }
+}
+
+/// Checks env data for problems, in the order of their lines
+///
+/// Finds lines that can't be parsed, keys defined more than once, keys that differ only by case,
+/// unquoted values with trailing whitespace, and keys that don't match `[A-Z_][A-Z0-9_]*`.
+///
+/// ```rust
+/// use simple_env_load::LintKind;
+///
+/// let lints = simple_env_load::lint("PORT=80\nlog_level=info\nPORT=8080 ");
+/// assert_eq!(lints.len(), 3);
+/// assert_eq!(lints[0].kind, LintKind::InvalidKey { key: "log_level".into() });
+/// assert_eq!(lints[1].kind, LintKind::Duplicate { key: "PORT".into(), first: 1 });
+/// assert_eq!(lints[2].to_string(), "3: the value of 'PORT' has trailing whitespace");
+/// ```
+pub fn lint(data: &str) -> Vec<Lint> {
+    let mut lints = vec![];
+    let (mut seen, mut cases) = (HashMap::new(), HashMap::new());
+    for (chunk, entry) in parse::chunks(data, Default::default()) {
+        let entry = match entry {
+            Ok(Some(entry)) => entry,
+            Ok(None) => continue,
+            Err(diagnostic) => {
+                lints.push(Lint {
+                    line: diagnostic.line,
+                    kind: LintKind::Malformed(diagnostic.reason),
+                });
+                continue;
+            }
+        };
+
+        let (line, key) = (entry.line, entry.key.to_string());
+        let written = parse::written_key(data, entry.key);
+        if !is_conventional(written) {
+            let kind = LintKind::InvalidKey {
+                key: written.to_string(),
+            };
+            lints.push(Lint { line, kind });
+        }
+        if let Some(&first) = seen.get(&key) {
+            let kind = LintKind::Duplicate {
+                key: key.clone(),
+                first,
+            };
+            lints.push(Lint { line, kind });
+        }
+        let (other, first) = cases
+            .entry(key.to_uppercase())
+            .or_insert((key.clone(), line));
+        if *other != key {
+            let kind = LintKind::CaseCollision {
+                key: key.clone(),
+                other: other.clone(),
+                first: *first,
+            };
+            lints.push(Lint { line, kind });
+        }
+        if !entry.is_quoted()
+            && chunk
+                .trim_end_matches(parse::LINE_BREAKS)
+                .ends_with([' ', '\t'])
+        {
+            let kind = LintKind::TrailingWhitespace { key: key.clone() };
+            lints.push(Lint { line, kind });
+        }
+        seen.entry(key).or_insert(line);
+    }
+    lints
+}
+
+/// Like [`lint`], also comparing the keys with an example file, such as `.env.example`
+///
+/// Keys that aren't in the example are [`Undocumented`](LintKind::Undocumented), and keys of
+/// the example that aren't defined are [`Missing`](LintKind::Missing), reported at their line in the example.
+///
+/// ```rust
+/// use simple_env_load::LintKind;
+///
+/// let lints = simple_env_load::lint_with_example("PORT=80\nDEBUG=1", "HOST=\nPORT=");
+/// assert_eq!(lints[0].kind, LintKind::Undocumented { key: "DEBUG".into() });
+/// assert_eq!(lints[1].line, 1);
+/// assert_eq!(lints[1].kind, LintKind::Missing { key: "HOST".into() });
+/// ```
+pub fn lint_with_example(data: &str, example: &str) -> Vec<Lint> {
+    let mut lints = lint(data);
+    let (undocumented, missing) = compare(data, example);
+    lints.extend(undocumented.into_iter().map(|(key, line)| Lint {
+        line,
+        kind: LintKind::Undocumented { key: key.into() },
+    }));
+    lints.extend(missing.into_iter().map(|(key, line)| Lint {
+        line,
+        kind: LintKind::Missing { key: key.into() },
+    }));
+    lints
+}
+
+/// The keys that differ between an env file and its example, see [`verify_against_example`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExampleDiff {
+    /// Keys in the example that the env file doesn't define, in the example's order
+    pub missing: Vec<String>,
+    /// Keys the env file defines that aren't in the example, in the env file's order
+    pub undocumented: Vec<String>,
+}
+
+impl ExampleDiff {
+    /// Whether the files define the same keys
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.undocumented.is_empty()
+    }
+}
+
+/// Compares the keys of an env file with an example file, such as `.env.example`
+///
+/// Only keys are compared, the example's values can be placeholders or empty.
+///
+/// ```rust
+/// # let dir = std::env::temp_dir();
+/// # std::fs::write(dir.join("verify_doc.env"), "PORT=8080\nDEBUG=1").unwrap();
+/// # std::fs::write(dir.join("verify_doc.env.example"), "# the database\nDB_URL=\nPORT=80").unwrap();
+/// let diff = simple_env_load::verify_against_example(
+///     dir.join("verify_doc.env"),
+///     dir.join("verify_doc.env.example"),
+/// )
+/// .unwrap();
+///
+/// assert_eq!(diff.missing, ["DB_URL"]);
+/// assert_eq!(diff.undocumented, ["DEBUG"]);
+/// # std::fs::remove_file(dir.join("verify_doc.env")).unwrap();
+/// # std::fs::remove_file(dir.join("verify_doc.env.example")).unwrap();
+/// ```
+pub fn verify_against_example(
+    env_path: impl AsRef<Path>,
+    example_path: impl AsRef<Path>,
+) -> Result<ExampleDiff, LoadError> {
+    let read = |path: &Path| {
+        let path = crate::path::expand(path);
+        std::fs::read_to_string(&path).map_err(|error| LoadError::Io { path, error })
+    };
+    let (data, example) = (read(env_path.as_ref())?, read(example_path.as_ref())?);
+
+    let (undocumented, missing) = compare(&data, &example);
+    let keys = |keys: Keys| keys.into_iter().map(|(key, _)| key.into()).collect();
+    Ok(ExampleDiff {
+        missing: keys(missing),
+        undocumented: keys(undocumented),
+    })
+}
+
+/// Keys with the line they're first defined on
+type Keys<'a> = Vec<(&'a str, usize)>;
+
+/// The keys that only `data` defines, and the keys that only `example` defines
+fn compare<'a>(data: &'a str, example: &'a str) -> (Keys<'a>, Keys<'a>) {
+    let keys = |data| {
+        let mut keys = Keys::new();
+        for entry in parse::parse(data, Default::default()) {
+            if !keys.iter().any(|&(key, _)| key == entry.key) {
+                keys.push((entry.key, entry.line))
+            }
+        }
+        keys
+    };
+    let (defined, documented) = (keys(data), keys(example));
+    let only = |a: &Keys<'a>, b: &Keys<'a>| {
+        a.iter()
+            .filter(|&&(key, _)| !b.iter().any(|&(k, _)| k == key))
+            .copied()
+            .collect()
+    };
+    (only(&defined, &documented), only(&documented, &defined))
+}
+
+/// Whether `key` matches `[A-Z_][A-Z0-9_]*`
+fn is_conventional(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_uppercase() || c == '_')
+        && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[test]
+fn lint_files() {
+    let data = "\
+# a comment
+A=1
+A=2
+B='unterminated
+C=\"quoted trailing \"
+D=unquoted\t
+9E=1
+a=3
+";
+    let lints = lint(data);
+    let lines = lints.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "3: 'A' was already defined on line 2",
+            "4: unterminated quote",
+            "6: the value of 'D' has trailing whitespace",
+            "7: '9E' should match [A-Z_][A-Z0-9_]*",
+            "8: 'a' should match [A-Z_][A-Z0-9_]*",
+            "8: 'a' differs only by case from 'A' on line 2",
+        ]
+    );
+    assert!(lint("").is_empty());
+
+    let lints = lint_with_example("A=1\nA=2", "# documented\nA=\nB=");
+    assert_eq!(lints.len(), 2);
+    assert_eq!(
+        lints[1],
+        Lint {
+            line: 3,
+            kind: LintKind::Missing { key: "B".into() }
+        }
+    );
+}
@@ -0,0 +1,180 @@
+/// How [`merge`] resolves a key that several sources define with different values
+#[non_exhaustive]
+pub enum MergeStrategy<'a> {
+    /// The value of the last source is kept
+    LastWins,
+    /// The value of the first source is kept
+    FirstWins,
+    /// Fail the merge with the first [`Conflict`]
+    Error,
+    /// `resolve(key, old, new)` produces the value to keep
+    Resolve(Box<Resolve<'a>>),
+}
+
+type Resolve<'a> = dyn Fn(&str, &str, &str) -> String + 'a;
+
+impl MergeStrategy<'_> {
+    /// A strategy that calls `resolve(key, old, new)` for each conflict
+    pub fn resolve<'a>(resolve: impl Fn(&str, &str, &str) -> String + 'a) -> MergeStrategy<'a> {
+        MergeStrategy::Resolve(Box::new(resolve))
+    }
+}
+
+impl std::fmt::Debug for MergeStrategy<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LastWins => f.write_str("LastWins"),
+            Self::FirstWins => f.write_str("FirstWins"),
+            Self::Error => f.write_str("Error"),
+            Self::Resolve(..) => f.write_str("Resolve"),
+        }
+    }
+}
+
+/// A key that two sources define with different values
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// The name of the variable
+    pub key: String,
+    /// The value it had so far
+    pub old: String,
+    /// The value of the source being merged
+    pub new: String,
+    /// The 0-based indices of the sources of `old` and `new`
+    pub sources: (usize, usize),
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (old, new) = self.sources;
+        write!(
+            f,
+            "'{}' is '{}' in source {old} and '{}' in source {new}",
+            self.key, self.old, self.new
+        )
+    }
+}
+
+impl std::error::Error for Conflict {}
+
+/// How a [`Conflict`] was resolved, see [`Merged::decisions`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decision {
+    /// The conflicting values
+    pub conflict: Conflict,
+    /// The value that was kept
+    pub kept: String,
+}
+
+/// The variables [`merge`] produced
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Merged {
+    /// The `(key, value)` pairs, in the order the keys were first defined
+    pub vars: Vec<(String, String)>,
+    /// How each conflict was resolved, in the order they were found
+    pub decisions: Vec<Decision>,
+}
+
+/// Merges the `(key, value)` pairs of several sources, resolving the keys they disagree on with `strategy`
+///
+/// Keys defined with the same value more than once aren't conflicts. A key defined twice in one
+/// source is a conflict with itself. Sources can be anything that produces pairs, such as
+/// [`parse_to_map`](crate::parse_to_map) or [`LoadReport::into_pairs`](crate::LoadReport::into_pairs).
+///
+/// ```rust
+/// use simple_env_load::{merge, parse_to_map, MergeStrategy};
+///
+/// let sources = [
+///     parse_to_map("HOST=localhost\nPORT=80"),
+///     parse_to_map("PORT=8080\nDEBUG=1"),
+/// ];
+///
+/// let merged = merge(sources.clone(), MergeStrategy::FirstWins).unwrap();
+/// assert_eq!(merged.vars[1], ("PORT".into(), "80".into()));
+/// assert_eq!(merged.decisions[0].conflict.new, "8080");
+///
+/// let merged = merge(sources.clone(), MergeStrategy::resolve(|_, old, new| format!("{old},{new}"))).unwrap();
+/// assert_eq!(merged.vars[1].1, "80,8080");
+///
+/// let err = merge(sources, MergeStrategy::Error).unwrap_err();
+/// assert_eq!(err.to_string(), "'PORT' is '80' in source 0 and '8080' in source 1");
+/// ```
+pub fn merge<I, S, K, V>(sources: I, strategy: MergeStrategy<'_>) -> Result<Merged, Conflict>
+where
+    I: IntoIterator<Item = S>,
+    S: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    let mut merged = Merged::default();
+    let mut seen = std::collections::HashMap::<String, (usize, usize)>::new();
+    for (source, pairs) in sources.into_iter().enumerate() {
+        for (key, value) in pairs {
+            let (key, new) = (key.into(), value.into());
+            let Some(&(i, from)) = seen.get(&key) else {
+                seen.insert(key.clone(), (merged.vars.len(), source));
+                merged.vars.push((key, new));
+                continue;
+            };
+            let old = &merged.vars[i].1;
+            if *old == new {
+                continue;
+            }
+
+            let conflict = Conflict {
+                key,
+                old: old.clone(),
+                new,
+                sources: (from, source),
+            };
+            let kept = match &strategy {
+                MergeStrategy::LastWins => conflict.new.clone(),
+                MergeStrategy::FirstWins => conflict.old.clone(),
+                MergeStrategy::Error => return Err(conflict),
+                MergeStrategy::Resolve(resolve) => {
+                    resolve(&conflict.key, &conflict.old, &conflict.new)
+                }
+            };
+            seen.insert(conflict.key.clone(), (i, source));
+            merged.vars[i].1.clone_from(&kept);
+            merged.decisions.push(Decision { conflict, kept });
+        }
+    }
+    Ok(merged)
+}
+
+#[test]
+fn merge_sources() {
+    let sources = || {
+        [
+            vec![("A", "1"), ("B", "2")],
+            vec![("B", "2"), ("A", "3")],
+            vec![("A", "4")],
+        ]
+    };
+
+    let merged = merge(sources(), MergeStrategy::LastWins).unwrap();
+    assert_eq!(
+        merged.vars,
+        [("A".into(), "4".into()), ("B".into(), "2".into())]
+    );
+    let sources_of = |merged: &Merged| {
+        let decisions = merged.decisions.iter();
+        decisions.map(|d| d.conflict.sources).collect::<Vec<_>>()
+    };
+    assert_eq!(sources_of(&merged), [(0, 1), (1, 2)]);
+
+    let merged = merge(sources(), MergeStrategy::FirstWins).unwrap();
+    assert_eq!(merged.vars[0].1, "1");
+    assert_eq!(sources_of(&merged), [(0, 1), (1, 2)]);
+    let old = merged.decisions.iter().map(|d| &*d.conflict.old);
+    assert_eq!(old.collect::<Vec<_>>(), ["1", "1"]);
+
+    let strategy = MergeStrategy::resolve(|_, old, new| old.max(new).to_string());
+    let merged = merge([vec![("A", "9"), ("A", "5")]], strategy).unwrap();
+    assert_eq!(merged.vars[0].1, "9");
+    assert_eq!(merged.decisions[0].conflict.sources, (0, 0));
+
+    assert!(merge(sources(), MergeStrategy::Error).is_err());
+    assert!(merge([[("A", "1")], [("A", "1")]], MergeStrategy::Error).is_ok());
+}
@@ -0,0 +1,99 @@
+use std::{
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{parse, path, Entry};
+
+/// An env file mapped into memory, for parsing large files without reading them into a `String`
+///
+/// Its entries borrow from the mapping, see [`parse_iter`](crate::parse_iter) for when values are copied.
+///
+/// ```rust
+/// # let path = std::env::temp_dir().join("mapped_file_doc.env");
+/// # std::fs::write(&path, "HOST=localhost\nPORT=8080").unwrap();
+/// // SAFETY: nothing else writes to the file while it's mapped
+/// let file = unsafe { simple_env_load::MappedFile::open(&path) }.unwrap();
+///
+/// let keys = file.parse_iter().map(|entry| entry.key).collect::<Vec<_>>();
+/// assert_eq!(keys, ["HOST", "PORT"]);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct MappedFile {
+    path: PathBuf,
+    map: memmap2::Mmap,
+}
+
+impl MappedFile {
+    /// Maps the file at `path`, failing if it can't be opened or isn't UTF-8
+    ///
+    /// Like [`Loader::path`](crate::Loader::path), a leading `~` is the home directory
+    /// and `$VAR`/`${VAR}` are taken from the process environment.
+    ///
+    /// # Safety
+    /// The file must not be changed, by this process or another one, while it's mapped.
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path::expand(path.as_ref());
+        let file = File::open(&path)?;
+        // SAFETY: the caller keeps the file from changing
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        if let Err(err) = std::str::from_utf8(&map) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+        Ok(Self { path, map })
+    }
+
+    /// The path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The contents of the file
+    pub fn as_str(&self) -> &str {
+        // SAFETY: it was checked to be UTF-8 when it was mapped
+        unsafe { std::str::from_utf8_unchecked(&self.map) }
+    }
+
+    /// Lazily parse the file, like [`parse_iter`](crate::parse_iter)
+    pub fn parse_iter(&self) -> impl Iterator<Item = Entry<'_>> + '_ {
+        parse::parse(self.as_str(), Default::default())
+    }
+}
+
+#[test]
+fn parse_mapped_file() {
+    let dir = std::env::temp_dir().join("simple_env_load_mmap");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let data = "A=1\nB=\"two\nlines\"\n\n# a comment\nC='$A'";
+    std::fs::write(dir.join(".env"), data).unwrap();
+    std::fs::write(dir.join("empty.env"), "").unwrap();
+    std::fs::write(dir.join("binary.env"), b"A=\xff").unwrap();
+
+    let file = unsafe { MappedFile::open(dir.join(".env")) }.unwrap();
+    assert_eq!(file.as_str(), data);
+    let entries = file
+        .parse_iter()
+        .map(|entry| (entry.key, entry.value))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            ("A", "1".into()),
+            ("B", "two\nlines".into()),
+            ("C", "$A".into())
+        ]
+    );
+
+    let empty = unsafe { MappedFile::open(dir.join("empty.env")) }.unwrap();
+    assert_eq!(empty.parse_iter().count(), 0);
+
+    let err = unsafe { MappedFile::open(dir.join("binary.env")) }.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = unsafe { MappedFile::open(dir.join("missing.env")) }.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+use crate::interpolate::{self, ExpandError, Ref};
+
+/// Expands a leading `~` to the home directory, and `$VAR`/`${VAR}` to the process environment
+///
+/// References to unset variables are left as is.
+pub(crate) fn expand(path: &Path) -> PathBuf {
+    let input = match path.to_str() {
+        Some(input) => input,
+        None => return path.to_path_buf(),
+    };
+
+    let input = expand_home(input);
+    let expanded = interpolate::expand(&input, false, true, |reference| match reference {
+        Ref::Var(name) => Ok::<_, ExpandError>(std::env::var(name).ok()),
+        Ref::Command(..) => Ok(None),
+    });
+    expanded.map_or_else(|_| input.into(), PathBuf::from)
+}
+
+/// Expands a leading `~` to the home directory, `~user` is left as is
+pub(crate) fn expand_home(input: &str) -> String {
+    match (input.strip_prefix('~'), home_dir()) {
+        (Some(rest), Some(home))
+            if rest.is_empty() || rest.starts_with(std::path::is_separator) =>
+        {
+            format!("{}{}", home.display(), rest)
+        }
+        _ => input.to_string(),
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+#[test]
+fn expand_paths() {
+    let home = home_dir().unwrap();
+    std::env::set_var("SIMPLE_ENV_LOAD_CONFIG", "/etc/app");
+    std::env::remove_var("SIMPLE_ENV_LOAD_UNSET");
+
+    assert_eq!(expand(Path::new("~/.env")), home.join(".env"));
+    assert_eq!(expand(Path::new("~")), home);
+    assert_eq!(expand(Path::new("~user/.env")), Path::new("~user/.env"));
+    assert_eq!(
+        expand(Path::new("$SIMPLE_ENV_LOAD_CONFIG/.env")),
+        Path::new("/etc/app/.env")
+    );
+    assert_eq!(
+        expand(Path::new("$SIMPLE_ENV_LOAD_UNSET/.env")),
+        Path::new("$SIMPLE_ENV_LOAD_UNSET/.env")
+    );
+    assert_eq!(
+        expand(Path::new("${SIMPLE_ENV_LOAD_UNSET}/.env")),
+        Path::new("${SIMPLE_ENV_LOAD_UNSET}/.env")
+    );
+}
@@ -0,0 +1,159 @@
+use crate::{Loader, Tree};
+
+/// The variables of a [`Loader`] as a layer of [figment](https://docs.rs/figment) or [config](https://docs.rs/config)
+///
+/// The files are read each time the framework asks for the variables, the process environment isn't changed.
+/// Like [`from_str`](crate::from_str), keys are lowercased and a `__` separates nested keys, so `DB__PORT`
+/// is `db.port`. A key with both a value and nested keys is a table, its own value is left out.
+///
+/// ```rust
+/// # #[cfg(feature = "figment")] {
+/// use simple_env_load::{EnvProvider, Loader};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     name: String,
+///     db: Database,
+/// }
+///
+/// #[derive(serde::Deserialize)]
+/// struct Database {
+///     port: u16,
+/// }
+///
+/// let loader = Loader::new().source("NAME=app\nDB__PORT=5432");
+/// let config: Config = figment::Figment::new()
+///     .merge(EnvProvider::new(loader))
+///     .extract()
+///     .unwrap();
+///
+/// assert_eq!((&*config.name, config.db.port), ("app", 5432));
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct EnvProvider {
+    loader: Loader,
+}
+
+impl EnvProvider {
+    /// A layer with the variables of `loader`
+    pub fn new(loader: Loader) -> Self {
+        Self { loader }
+    }
+
+    /// The variables, grouped into tables
+    fn tree(&self) -> Result<Tree, crate::LoadError> {
+        fn tables(tree: &mut Tree) {
+            if !tree.children.is_empty() {
+                tree.value = None;
+            }
+            tree.children.values_mut().for_each(tables);
+        }
+
+        let pairs = self.loader.evaluate_all()?.into_pairs();
+        let pairs = pairs
+            .into_iter()
+            .map(|(key, value)| (key.to_lowercase(), value));
+        let mut tree = Tree::from_pairs(pairs);
+        tables(&mut tree);
+        Ok(tree)
+    }
+}
+
+impl From<Loader> for EnvProvider {
+    fn from(loader: Loader) -> Self {
+        Self::new(loader)
+    }
+}
+
+#[cfg(feature = "figment")]
+impl figment::Provider for EnvProvider {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::named("env files")
+    }
+
+    fn data(
+        &self,
+    ) -> Result<figment::value::Map<figment::Profile, figment::value::Dict>, figment::Error> {
+        fn dict(tree: &Tree) -> figment::value::Dict {
+            let value = |tree: &Tree| match tree.children.is_empty() {
+                // parsed like figment's own `Env`, so `5432` is a number
+                true => tree.value.as_deref().unwrap_or_default().parse().unwrap(),
+                false => dict(tree).into(),
+            };
+            let children = tree.children.iter();
+            children
+                .map(|(key, tree)| (key.clone(), value(tree)))
+                .collect()
+        }
+
+        let tree = self.tree().map_err(|err| err.to_string())?;
+        Ok(figment::Profile::Default.collect(dict(&tree)))
+    }
+}
+
+#[cfg(feature = "config")]
+impl config::Source for EnvProvider {
+    fn clone_into_box(&self) -> Box<dyn config::Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<config::Map<String, config::Value>, config::ConfigError> {
+        let tree = self
+            .tree()
+            .map_err(|err| config::ConfigError::Message(err.to_string()))?;
+
+        let origin = "env files".to_string();
+        let pairs = tree.flatten_with(".").into_iter();
+        Ok(pairs
+            .map(|(key, value)| (key, config::Value::new(Some(&origin), value)))
+            .collect())
+    }
+}
+
+#[test]
+fn provide_layers() {
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        db: Database,
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Database {
+        host: String,
+        port: u16,
+    }
+
+    let source = "NAME=app\nDB=ignored\nDB__HOST=localhost\nDB__PORT=5432";
+    let provider = EnvProvider::new(Loader::new().source(source));
+    let expected = Config {
+        name: "app".into(),
+        db: Database {
+            host: "localhost".into(),
+            port: 5432,
+        },
+    };
+
+    let tree = provider.tree().unwrap();
+    assert_eq!(tree.get("db").unwrap().value, None);
+
+    #[cfg(feature = "figment")]
+    {
+        let config = figment::Figment::from(provider.clone())
+            .extract::<Config>()
+            .unwrap();
+        assert_eq!(config, expected);
+    }
+
+    #[cfg(feature = "config")]
+    {
+        let config = config::Config::builder()
+            .add_source(provider)
+            .build()
+            .unwrap()
+            .try_deserialize::<Config>()
+            .unwrap();
+        assert_eq!(config, expected);
+    }
+}
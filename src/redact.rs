@@ -0,0 +1,185 @@
+use std::fmt;
+
+use crate::LoadReport;
+
+/// What [`Redacted`] and [`RedactedReport`] show instead of a value
+const MASK: &str = "****";
+
+/// A value that [`Debug`](fmt::Debug) and [`Display`](fmt::Display) show as `****`, so it can't be logged by accident
+///
+/// ```rust
+/// use simple_env_load::Redacted;
+///
+/// let password = Redacted::new(String::from("hunter2"));
+/// assert_eq!(format!("{password} {password:?}"), "**** ****");
+/// assert_eq!(password.expose(), "hunter2");
+/// ```
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Redacted<T>(T);
+
+impl<T> Redacted<T> {
+    /// Wraps a value
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// The value
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwraps the value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Redacted<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(MASK)
+    }
+}
+
+impl<T> fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(MASK)
+    }
+}
+
+/// A [`LoadReport`] that masks the values of secret-looking keys when it's printed, see [`LoadReport::redacted`]
+///
+/// [`Display`](fmt::Display) writes a `KEY=value (path:line)` line per variable, [`Debug`](fmt::Debug) a map of them.
+#[derive(Clone)]
+pub struct RedactedReport<'a> {
+    report: &'a LoadReport,
+    patterns: Vec<String>,
+}
+
+impl<'a> RedactedReport<'a> {
+    /// The patterns of the keys whose values are masked by default
+    pub const DEFAULT_PATTERNS: &'static [&'static str] = &[
+        "*secret*",
+        "*token*",
+        "*password*",
+        "*passwd*",
+        "*credential*",
+        "*api_key*",
+        "*apikey*",
+        "*private_key*",
+    ];
+
+    pub(crate) fn new(report: &'a LoadReport) -> Self {
+        Self {
+            report,
+            patterns: Self::DEFAULT_PATTERNS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+
+    /// Also mask the values of keys matching `pattern`
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.patterns.push(pattern.into());
+        self
+    }
+
+    /// Replace the patterns (default: [`RedactedReport::DEFAULT_PATTERNS`])
+    pub fn patterns<I, T>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether the value of `key` is masked
+    ///
+    /// Patterns are globs, with `*`, `?` and `[...]`, compared ignoring ASCII case.
+    pub fn is_redacted(&self, key: &str) -> bool {
+        let key = key.to_ascii_lowercase();
+        (self.patterns.iter())
+            .any(|pattern| crate::glob::matches(&pattern.to_ascii_lowercase(), &key))
+    }
+
+    fn vars(&self) -> impl Iterator<Item = (&str, &str, &crate::Origin)> + '_ {
+        self.report.vars().iter().map(|var| {
+            let value = match self.is_redacted(&var.key) {
+                true => MASK,
+                false => &var.value,
+            };
+            (&*var.key, value, &var.origin)
+        })
+    }
+}
+
+impl fmt::Display for RedactedReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.vars()
+            .enumerate()
+            .try_for_each(|(i, (key, value, origin))| {
+                let sep = if i == 0 { "" } else { "\n" };
+                write!(f, "{sep}{key}={value} ({origin})")
+            })
+    }
+}
+
+impl fmt::Debug for RedactedReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.vars().map(|(key, value, _)| (key, value)))
+            .finish()
+    }
+}
+
+impl LoadReport {
+    /// The report, masking the values of secret-looking keys when it's printed
+    ///
+    /// ```rust
+    /// let report = simple_env_load::Loader::new()
+    ///     .source("REDACT_DOC_HOST=localhost\nREDACT_DOC_DB_PASSWORD=hunter2\nREDACT_DOC_PIN=1234")
+    ///     .load()
+    ///     .unwrap();
+    ///
+    /// let redacted = report.redacted().pattern("*_pin");
+    /// assert_eq!(
+    ///     redacted.to_string(),
+    ///     "REDACT_DOC_HOST=localhost (<string>:1)\n\
+    ///     REDACT_DOC_DB_PASSWORD=**** (<string>:2)\n\
+    ///     REDACT_DOC_PIN=**** (<string>:3)"
+    /// );
+    /// assert_eq!(
+    ///     format!("{redacted:?}"),
+    ///     r#"{"REDACT_DOC_HOST": "localhost", "REDACT_DOC_DB_PASSWORD": "****", "REDACT_DOC_PIN": "****"}"#
+    /// );
+    /// ```
+    pub fn redacted(&self) -> RedactedReport<'_> {
+        RedactedReport::new(self)
+    }
+}
+
+#[test]
+fn redact_values() {
+    let report = LoadReport::default();
+    let redacted = report.redacted();
+    assert!(redacted.is_redacted("GITHUB_TOKEN"));
+    assert!(redacted.is_redacted("aws_secret_access_key"));
+    assert!(redacted.is_redacted("Db_Password"));
+    assert!(!redacted.is_redacted("PORT"));
+    assert_eq!(redacted.to_string(), "");
+
+    let redacted = redacted.patterns(["[A-C]*"]);
+    assert!(redacted.is_redacted("b_var"));
+    assert!(!redacted.is_redacted("GITHUB_TOKEN"));
+
+    let value = Redacted::from(42);
+    assert_eq!(format!("{value:?}"), "****");
+    assert_eq!(value.into_inner(), 42);
+}
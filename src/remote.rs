@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use crate::{LoadError, Source, SourceError};
+
+/// An env file fetched over HTTP(S), see [`Loader::url`](crate::Loader::url)
+///
+/// Requests that fail to connect, time out or get a `5xx`/`429` response are retried,
+/// waiting twice as long before each retry, starting at 250ms.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use simple_env_load::{Loader, Remote};
+///
+/// let token = std::env::var("CONFIG_TOKEN").unwrap();
+/// Loader::new()
+///     .path(".env")
+///     .url(
+///         Remote::new("https://config.example.com/app.env")
+///             .bearer_token(token)
+///             .timeout(Duration::from_secs(5))
+///             .retries(3),
+///     )
+///     .load()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Remote {
+    pub(crate) url: String,
+    token: Option<String>,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl Remote {
+    /// Fetch `url`, with a 30 second timeout and 2 retries
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            token: None,
+            timeout: Duration::from_secs(30),
+            retries: 2,
+        }
+    }
+
+    /// Send `token` in an `Authorization: Bearer` header
+    pub fn bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// How long each attempt can take, from connecting to reading the whole body (default: 30s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How many times a failed request is retried (default: 2)
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// The url that is fetched
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Fetch the env file, blocking until it's read or every attempt failed
+    pub fn fetch(&self) -> Result<String, LoadError> {
+        self.fetch_limited(None)
+    }
+
+    /// Like [`fetch`](Self::fetch), reading no more than `limit` bytes of the response
+    pub(crate) fn fetch_limited(&self, limit: Option<u64>) -> Result<String, LoadError> {
+        self.get(limit).map_err(|error| LoadError::Remote {
+            url: self.url.clone(),
+            error,
+        })
+    }
+
+    pub(crate) fn get(
+        &self,
+        limit: Option<u64>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let agent = ureq::AgentBuilder::new().timeout(self.timeout).build();
+        let mut attempt = 0;
+        loop {
+            let mut request = agent.get(&self.url);
+            if let Some(token) = &self.token {
+                request = request.set("Authorization", &format!("Bearer {token}"));
+            }
+
+            let error = match request.call() {
+                Ok(response) if limit.is_some() => {
+                    let reader = response.into_reader();
+                    return Ok(crate::limits::read_to_string_limited(reader, limit)?);
+                }
+                Ok(response) => return Ok(response.into_string()?),
+                Err(err) if !retryable(&err) => return Err(err.into()),
+                Err(err) => err,
+            };
+            if attempt >= self.retries {
+                return Err(error.into());
+            }
+            std::thread::sleep(backoff(attempt));
+            attempt += 1;
+        }
+    }
+}
+
+impl Source for Remote {
+    fn read(&self) -> Result<String, SourceError> {
+        self.get(None).map_err(SourceError::new)
+    }
+
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        self.get(Some(limit)).map_err(SourceError::new)
+    }
+
+    fn name(&self) -> String {
+        self.url.clone()
+    }
+}
+
+impl std::fmt::Debug for Remote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Remote")
+            .field("url", &self.url)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("timeout", &self.timeout)
+            .field("retries", &self.retries)
+            .finish()
+    }
+}
+
+impl From<&str> for Remote {
+    fn from(url: &str) -> Self {
+        Self::new(url)
+    }
+}
+
+impl From<String> for Remote {
+    fn from(url: String) -> Self {
+        Self::new(url)
+    }
+}
+
+fn retryable(err: &ureq::Error) -> bool {
+    match err {
+        ureq::Error::Status(code, _) => *code >= 500 || *code == 429,
+        ureq::Error::Transport(..) => true,
+    }
+}
+
+/// How long to wait before retrying after the failed `attempt`
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(250) * 2u32.saturating_pow(attempt.min(8))
+}
+
+#[test]
+fn remote_retries() {
+    assert_eq!(backoff(0), Duration::from_millis(250));
+    assert_eq!(backoff(2), Duration::from_secs(1));
+    assert_eq!(backoff(20), backoff(8));
+
+    let remote = Remote::from("http://127.0.0.1:1/app.env")
+        .bearer_token("secret")
+        .timeout(Duration::from_millis(200))
+        .retries(0);
+    assert!(!format!("{remote:?}").contains("secret"));
+
+    let err = remote.fetch().unwrap_err();
+    assert!(matches!(&err, LoadError::Remote { url, .. } if url == remote.url()));
+    assert!(err
+        .to_string()
+        .starts_with("cannot fetch 'http://127.0.0.1:1/app.env': "));
+}
@@ -0,0 +1,219 @@
+use std::path::PathBuf;
+
+/// Where a variable was loaded from
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Origin {
+    /// The file the variable was defined in
+    pub path: PathBuf,
+    /// The 1-based line the definition started on
+    pub line: usize,
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)
+    }
+}
+
+/// A variable that was set by a [`Loader`](crate::Loader)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoadedVar {
+    /// The name of the variable
+    pub key: String,
+    /// The value it was set to
+    pub value: String,
+    /// Where it was defined
+    pub origin: Origin,
+}
+
+/// A key that was defined more than once, see [`Loader::duplicates`](crate::Loader::duplicates)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Duplicate {
+    /// The name of the variable
+    pub key: String,
+    /// Every place it was defined, in order
+    pub origins: Vec<Origin>,
+}
+
+/// A key that isn't a valid name, see [`Loader::key_policy`](crate::Loader::key_policy)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidKey {
+    /// The key as it was written, with its quotes if it was quoted
+    pub key: String,
+    /// Where it was defined
+    pub origin: Origin,
+    /// The key it was replaced with, with [`KeyPolicy::Sanitize`](crate::KeyPolicy::Sanitize)
+    pub sanitized: Option<String>,
+}
+
+/// Counts of what a [`Loader`](crate::Loader) did, see [`LoadReport::stats`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadStats {
+    /// The files and sources that were read, or would have been if they existed, including the included files
+    pub files_attempted: usize,
+    /// The optional files that didn't exist
+    pub files_missing: usize,
+    /// The bytes of env data that were read, after decoding and decrypting
+    pub bytes_read: usize,
+    /// The lines that weren't blank, comments or in an inactive section, a multiline value counts once
+    pub lines_parsed: usize,
+    /// The variables that were set
+    pub entries_set: usize,
+    /// The entries that weren't set, such as malformed lines, denied keys or variables that were already set
+    pub entries_skipped: usize,
+    /// How long reading and evaluating the files took
+    pub duration: std::time::Duration,
+}
+
+/// What changed between two loads, see [`Loader::watch`](crate::Loader::watch)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// The variables that weren't defined before
+    pub added: Vec<LoadedVar>,
+    /// The variables that have a new value
+    pub changed: Vec<LoadedVar>,
+    /// The keys that are no longer defined
+    pub removed: Vec<String>,
+}
+
+impl Changes {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    /// Compares the last definition of each key
+    #[cfg_attr(
+        not(any(feature = "watch", all(unix, feature = "signal"))),
+        allow(dead_code)
+    )]
+    pub(crate) fn between(old: &LoadReport, new: &LoadReport) -> Self {
+        fn latest(report: &LoadReport) -> std::collections::BTreeMap<&str, &LoadedVar> {
+            report.vars.iter().map(|var| (&*var.key, var)).collect()
+        }
+        let (old, new) = (latest(old), latest(new));
+
+        let mut changes = Self::default();
+        for (key, var) in &new {
+            match old.get(key) {
+                None => changes.added.push((*var).clone()),
+                Some(old) if old.value != var.value => changes.changed.push((*var).clone()),
+                Some(..) => {}
+            }
+        }
+        changes.removed = old
+            .keys()
+            .filter(|key| !new.contains_key(*key))
+            .map(ToString::to_string)
+            .collect();
+        changes
+    }
+}
+
+/// What a [`Loader`](crate::Loader) did
+///
+/// ```rust
+/// # let dir = std::env::temp_dir();
+/// # std::fs::write(dir.join("report_base.env"), "REPORT_HOST=localhost\nREPORT_PORT=80").unwrap();
+/// # std::fs::write(dir.join("report_local.env"), "\nREPORT_PORT=8080").unwrap();
+/// let report = simple_env_load::Loader::new()
+///     .path(dir.join("report_base.env"))
+///     .path(dir.join("report_local.env"))
+///     .load()
+///     .unwrap();
+///
+/// let origin = report.origin("REPORT_PORT").unwrap();
+/// assert_eq!(origin.path, dir.join("report_local.env"));
+/// assert_eq!(origin.line, 2);
+///
+/// // print the provenance of everything that was loaded
+/// for (key, origin) in report.origins() {
+///     println!("{key} from {origin}");
+/// }
+/// # std::fs::remove_file(dir.join("report_base.env")).unwrap();
+/// # std::fs::remove_file(dir.join("report_local.env")).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    pub(crate) vars: Vec<LoadedVar>,
+    pub(crate) insecure: Vec<PathBuf>,
+    pub(crate) duplicates: Vec<Duplicate>,
+    pub(crate) invalid_keys: Vec<InvalidKey>,
+    pub(crate) unset: Vec<String>,
+    pub(crate) stats: LoadStats,
+}
+
+impl LoadReport {
+    /// Every variable that was set, in the order they were applied
+    ///
+    /// A key defined several times appears once per definition
+    pub fn vars(&self) -> &[LoadedVar] {
+        &self.vars
+    }
+
+    /// Where a key was last set from
+    pub fn origin(&self, key: &str) -> Option<&Origin> {
+        self.vars
+            .iter()
+            .rev()
+            .find(|var| var.key == key)
+            .map(|var| &var.origin)
+    }
+
+    /// Where each key was last set from, in the order the keys were first set
+    pub fn origins(&self) -> impl Iterator<Item = (&str, &Origin)> + '_ {
+        let mut seen = std::collections::HashSet::new();
+        self.vars
+            .iter()
+            .filter(move |var| seen.insert(&*var.key))
+            .map(move |var| (&*var.key, self.origin(&var.key).unwrap_or(&var.origin)))
+    }
+
+    /// The files that are readable by their group or by others, see [`Loader::audit_permissions`](crate::Loader::audit_permissions)
+    pub fn insecure_files(&self) -> &[PathBuf] {
+        &self.insecure
+    }
+
+    /// The keys that were defined more than once, in the order they were first defined
+    pub fn duplicates(&self) -> &[Duplicate] {
+        &self.duplicates
+    }
+
+    /// The keys that aren't valid names, in the order they were defined, see [`Loader::key_policy`](crate::Loader::key_policy)
+    pub fn invalid_keys(&self) -> &[InvalidKey] {
+        &self.invalid_keys
+    }
+
+    /// The keys that were removed by an `unset KEY` line, see [`Loader::unset_directives`](crate::Loader::unset_directives)
+    pub fn unset_keys(&self) -> &[String] {
+        &self.unset
+    }
+
+    /// Counts of the files that were read and the entries that were set, e.g. to export as metrics
+    ///
+    /// ```rust
+    /// let report = simple_env_load::Loader::new()
+    ///     .source("STATS_DOC_A=1\nmalformed\nPATH=/tmp")
+    ///     .optional_path("stats_doc_missing.env")
+    ///     .load()
+    ///     .unwrap();
+    ///
+    /// let stats = report.stats();
+    /// assert_eq!((stats.files_attempted, stats.files_missing), (2, 1));
+    /// assert_eq!((stats.lines_parsed, stats.entries_set, stats.entries_skipped), (3, 1, 2));
+    /// if stats.entries_set == 0 {
+    ///     eprintln!("warning: no variables were loaded");
+    /// }
+    /// ```
+    pub fn stats(&self) -> &LoadStats {
+        &self.stats
+    }
+
+    /// The `(key, value)` pairs that were set, in the order they were applied
+    pub fn into_pairs(self) -> Vec<(String, String)> {
+        self.vars
+            .into_iter()
+            .map(|var| (var.key, var.value))
+            .collect()
+    }
+}
@@ -0,0 +1,116 @@
+use crate::LoadReport;
+
+/// Check that every key is set in the environment of this process, so startup fails once with all of them
+///
+/// ```rust
+/// std::env::set_var("REQUIRE_DOC_HOST", "localhost");
+///
+/// let err = simple_env_load::require(["REQUIRE_DOC_HOST", "REQUIRE_DOC_A", "REQUIRE_DOC_B"]).unwrap_err();
+/// assert_eq!(err.keys(), ["REQUIRE_DOC_A", "REQUIRE_DOC_B"]);
+/// assert_eq!(err.to_string(), "'REQUIRE_DOC_A' and 'REQUIRE_DOC_B' are required, but not set");
+/// ```
+pub fn require<I>(keys: I) -> Result<(), MissingKeys>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    check(keys, |key| std::env::var_os(key).is_some())
+}
+
+impl LoadReport {
+    /// Check that every key was set by the loaded files, see [`require`] to check the environment instead
+    ///
+    /// ```rust
+    /// let report = simple_env_load::Loader::new()
+    ///     .source("DATABASE_URL=postgres://localhost/app")
+    ///     .backend(simple_env_load::Backend::Store)
+    ///     .load()
+    ///     .unwrap();
+    ///
+    /// let err = report.require(["DATABASE_URL", "REDIS_URL"]).unwrap_err();
+    /// assert_eq!(err.to_string(), "'REDIS_URL' is required, but not set");
+    /// ```
+    pub fn require<I>(&self, keys: I) -> Result<(), MissingKeys>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        check(keys, |key| self.vars.iter().any(|var| var.key == key))
+    }
+}
+
+fn check<I>(keys: I, is_set: impl Fn(&str) -> bool) -> Result<(), MissingKeys>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut missing = vec![];
+    for key in keys {
+        let key = key.as_ref();
+        if !is_set(key) && !missing.iter().any(|k| k == key) {
+            missing.push(key.to_string());
+        }
+    }
+    match missing.is_empty() {
+        true => Ok(()),
+        false => Err(MissingKeys { keys: missing }),
+    }
+}
+
+/// The required keys that aren't set, see [`require`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingKeys {
+    keys: Vec<String>,
+}
+
+impl MissingKeys {
+    /// The keys that aren't set, in the order they were required
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+}
+
+impl std::fmt::Display for MissingKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &*self.keys {
+            [key] => write!(f, "'{key}' is required, but not set"),
+            [keys @ .., last] => {
+                let keys = keys.iter().map(|key| format!("'{key}'"));
+                write!(
+                    f,
+                    "{} and '{last}' are required, but not set",
+                    keys.collect::<Vec<_>>().join(", ")
+                )
+            }
+            [] => write!(f, "no keys are missing"),
+        }
+    }
+}
+
+impl std::error::Error for MissingKeys {}
+
+#[test]
+fn require_keys() {
+    let report = LoadReport {
+        vars: ["A", "B"]
+            .map(|key| crate::LoadedVar {
+                key: key.into(),
+                value: String::new(),
+                origin: crate::Origin {
+                    path: "a.env".into(),
+                    line: 1,
+                },
+            })
+            .to_vec(),
+        ..Default::default()
+    };
+    assert!(report.require(["A", "B", "A"]).is_ok());
+    assert!(report.require::<[&str; 0]>([]).is_ok());
+
+    let err = report.require(vec!["C", "A", "D", "C", "E"]).unwrap_err();
+    assert_eq!(err.keys(), ["C", "D", "E"]);
+    assert_eq!(
+        err.to_string(),
+        "'C', 'D' and 'E' are required, but not set"
+    );
+}
@@ -0,0 +1,345 @@
+use crate::env::{parse_bool, parse_bytes, parse_duration};
+
+/// The keys an application expects, and what their values must look like
+///
+/// [`validate`](Self::validate) checks every key at once, so all of the misconfiguration is reported together.
+///
+/// ```rust
+/// use simple_env_load::{Rule, Schema, Type};
+///
+/// let schema = Schema::new()
+///     .key("DATABASE_URL", Rule::required().ty(Type::Url))
+///     .key("PORT", Rule::optional().ty(Type::Int).range(1, 65535))
+///     .key("LOG_LEVEL", Rule::optional().one_of(["debug", "info", "warn"]));
+///
+/// let vars = simple_env_load::parse_to_map("PORT=80000\nLOG_LEVEL=trace");
+/// let err = schema.validate(&vars).unwrap_err();
+/// assert_eq!(err.violations().len(), 3);
+/// assert_eq!(
+///     err.to_string(),
+///     "3 invalid variables:\n\
+///     \x20 'DATABASE_URL' is required\n\
+///     \x20 invalid value '80000' for 'PORT': expected at most 65535\n\
+///     \x20 invalid value 'trace' for 'LOG_LEVEL': expected one of debug, info, warn"
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Schema {
+    keys: Vec<(String, Rule)>,
+}
+
+impl Schema {
+    /// A schema without any keys
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a key, replacing its rule if it was already declared
+    pub fn key(mut self, key: impl Into<String>, rule: Rule) -> Self {
+        let key = key.into();
+        match self.keys.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, old)) => *old = rule,
+            None => self.keys.push((key, rule)),
+        }
+        self
+    }
+
+    /// The declared keys and their rules, in the order they were declared
+    pub fn keys(&self) -> impl Iterator<Item = (&str, &Rule)> + '_ {
+        self.keys.iter().map(|(key, rule)| (key.as_str(), rule))
+    }
+
+    /// Check the variables, such as [`parse_to_map`](crate::parse_to_map) or [`LoadReport::into_pairs`](crate::LoadReport::into_pairs) produce
+    ///
+    /// When a key is in `vars` more than once, the last one is checked.
+    /// Keys that aren't declared are ignored.
+    pub fn validate<K, V>(&self, vars: &[(K, V)]) -> Result<(), SchemaError>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let violations = self
+            .keys
+            .iter()
+            .filter_map(
+                |(key, rule)| match vars.iter().rev().find(|(k, _)| k.as_ref() == key) {
+                    Some((_, value)) => Some(Violation::Invalid {
+                        reason: rule.check(value.as_ref()).err()?,
+                        key: key.clone(),
+                        value: value.as_ref().to_string(),
+                    }),
+                    None => rule
+                        .required
+                        .then(|| Violation::Missing { key: key.clone() }),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(SchemaError { violations }),
+        }
+    }
+}
+
+/// What a key's value must look like, see [`Schema::key`]
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub(crate) required: bool,
+    ty: Type,
+    range: Option<(i64, i64)>,
+    allowed: Vec<String>,
+    #[cfg(feature = "regex")]
+    pattern: Option<regex::Regex>,
+}
+
+impl Rule {
+    /// A key that must be set
+    pub fn required() -> Self {
+        Self {
+            required: true,
+            ty: Type::String,
+            range: None,
+            allowed: vec![],
+            #[cfg(feature = "regex")]
+            pattern: None,
+        }
+    }
+
+    /// A key that can be left out, it's only checked when it's set
+    pub fn optional() -> Self {
+        Self {
+            required: false,
+            ..Self::required()
+        }
+    }
+
+    /// What type the value is (default: [`Type::String`])
+    pub fn ty(mut self, ty: Type) -> Self {
+        self.ty = ty;
+        self
+    }
+
+    /// The smallest and largest values a [`Type::Int`] can be, inclusive
+    pub fn range(mut self, min: i64, max: i64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// The only values the key can have, compared exactly
+    pub fn one_of<I, T>(mut self, allowed: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.allowed = allowed.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// A regular expression the value must match, anchor it with `^` and `$` to match the whole value
+    #[cfg(feature = "regex")]
+    pub fn pattern(mut self, pattern: regex::Regex) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Whether the key must be set
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Checks a value against the rule, producing why it doesn't follow it
+    fn check(&self, value: &str) -> Result<(), String> {
+        self.ty.check(value)?;
+
+        if let (Some((min, max)), Type::Int) = (self.range, self.ty) {
+            let value = value.trim().parse::<i64>().map_err(|err| err.to_string())?;
+            if value < min {
+                return Err(format!("expected at least {min}"));
+            }
+            if value > max {
+                return Err(format!("expected at most {max}"));
+            }
+        }
+
+        if !self.allowed.is_empty() && !self.allowed.iter().any(|allowed| allowed == value) {
+            return Err(format!("expected one of {}", self.allowed.join(", ")));
+        }
+
+        #[cfg(feature = "regex")]
+        if let Some(pattern) = self.pattern.as_ref().filter(|p| !p.is_match(value)) {
+            return Err(format!("expected a match for '{pattern}'"));
+        }
+        Ok(())
+    }
+}
+
+/// The type of a value, see [`Rule::ty`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Type {
+    /// Anything
+    #[default]
+    String,
+    /// A whole number that fits an `i64`
+    Int,
+    /// A floating point number
+    Float,
+    /// `true`/`false`, `1`/`0`, `yes`/`no` or `on`/`off`, like [`Env::get_bool`](crate::Env::get_bool)
+    Bool,
+    /// A URL with a scheme, e.g. `postgres://localhost/app`
+    Url,
+    /// A duration, e.g. `30s` or `1h30m`, like [`Env::get_duration`](crate::Env::get_duration)
+    Duration,
+    /// A number of bytes, e.g. `512MB`, like [`Env::get_bytes`](crate::Env::get_bytes)
+    Bytes,
+}
+
+impl Type {
+    fn check(self, value: &str) -> Result<(), String> {
+        match self {
+            Self::String => Ok(()),
+            Self::Int => value
+                .trim()
+                .parse::<i64>()
+                .map(drop)
+                .map_err(|_| "expected an integer".into()),
+            Self::Float => value
+                .trim()
+                .parse::<f64>()
+                .map(drop)
+                .map_err(|_| "expected a number".into()),
+            Self::Bool => parse_bool(value).map(drop),
+            Self::Url => match is_url(value.trim()) {
+                true => Ok(()),
+                false => Err("expected a URL".into()),
+            },
+            Self::Duration => parse_duration(value).map(drop),
+            Self::Bytes => parse_bytes(value).map(drop),
+        }
+    }
+}
+
+/// Whether `value` is `scheme://` followed by something, the scheme being a letter then letters, digits, `+`, `-` or `.`
+fn is_url(value: &str) -> bool {
+    let Some((scheme, rest)) = value.split_once("://") else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !rest.contains(char::is_whitespace)
+}
+
+/// A key that doesn't follow its [`Rule`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Violation {
+    /// A required key isn't set
+    Missing {
+        /// The key
+        key: String,
+    },
+    /// A value doesn't follow the rule
+    Invalid {
+        /// The key
+        key: String,
+        /// Its value
+        value: String,
+        /// Why it doesn't follow the rule
+        reason: String,
+    },
+}
+
+impl Violation {
+    /// The key that doesn't follow its rule
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Missing { key } | Self::Invalid { key, .. } => key,
+        }
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { key } => write!(f, "'{key}' is required"),
+            Self::Invalid { key, value, reason } => {
+                write!(f, "invalid value '{value}' for '{key}': {reason}")
+            }
+        }
+    }
+}
+
+/// Every key that doesn't follow its rule, see [`Schema::validate`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaError {
+    violations: Vec<Violation>,
+}
+
+impl SchemaError {
+    /// The keys that don't follow their rule, in the order they were declared
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &*self.violations {
+            [violation] => write!(f, "{violation}"),
+            violations => {
+                write!(f, "{} invalid variables:", violations.len())?;
+                violations
+                    .iter()
+                    .try_for_each(|violation| write!(f, "\n  {violation}"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+#[test]
+fn validate_schema() {
+    let schema = Schema::new()
+        .key("A", Rule::required().ty(Type::Bool))
+        .key("B", Rule::optional().ty(Type::Duration))
+        .key("C", Rule::optional().ty(Type::Url))
+        .key("D", Rule::optional().ty(Type::Int).range(0, 10))
+        .key("A", Rule::optional().ty(Type::Bool));
+    assert_eq!(schema.keys().count(), 4);
+    assert!(schema.validate::<&str, &str>(&[]).is_ok());
+
+    let vars = [
+        ("A", "maybe"),
+        ("B", "1h"),
+        ("C", "redis://cache:6379/0"),
+        ("D", "-1"),
+        ("D", "3"),
+    ];
+    let err = schema.validate(&vars).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value 'maybe' for 'A': expected a boolean"
+    );
+
+    let err = schema
+        .validate(&[("C", "localhost:6379"), ("D", "eleven")])
+        .unwrap_err();
+    let keys = err
+        .violations()
+        .iter()
+        .map(Violation::key)
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["C", "D"]);
+
+    #[cfg(feature = "regex")]
+    {
+        let pattern = regex::Regex::new("^[a-z]+$").unwrap();
+        let schema = Schema::new().key("E", Rule::required().pattern(pattern));
+        assert!(schema.validate(&[("E", "abc")]).is_ok());
+        assert!(schema.validate(&[("E", "ABC")]).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use crate::{Source, SourceError};
+
+/// A directory of single value files, like Docker's `/run/secrets` or a Kubernetes secret volume
+///
+/// Each file's name is a key, and its contents without the trailing newlines are the value.
+/// Files starting with a `.` and directories are skipped, symlinks are followed. Values are taken literally,
+/// they aren't expanded. See [`Loader::source`](crate::Loader::source).
+///
+/// ```rust
+/// use simple_env_load::{Loader, SecretsDir};
+/// # let dir = std::env::temp_dir().join("secrets_dir_doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # std::fs::write(dir.join("secrets_dir_doc_password"), "hunter2\n").unwrap();
+///
+/// Loader::new()
+///     .source(SecretsDir::new(&dir).uppercase_keys(true))
+///     .load()
+///     .unwrap();
+///
+/// assert_eq!(std::env::var("SECRETS_DIR_DOC_PASSWORD").unwrap(), "hunter2");
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct SecretsDir {
+    path: PathBuf,
+    uppercase_keys: bool,
+}
+
+impl SecretsDir {
+    /// Read the files in the directory at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            uppercase_keys: false,
+        }
+    }
+
+    /// Whether file names are uppercased to make the keys, e.g. `db_password` is `DB_PASSWORD` (default: `false`)
+    pub fn uppercase_keys(mut self, uppercase_keys: bool) -> Self {
+        self.uppercase_keys = uppercase_keys;
+        self
+    }
+}
+
+impl Source for SecretsDir {
+    fn read(&self) -> Result<String, SourceError> {
+        let dir = crate::path::expand(&self.path);
+        let mut pairs = vec![];
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') || !entry.path().is_file() {
+                continue;
+            }
+
+            let value = std::fs::read_to_string(entry.path())?;
+            let value = value.trim_end_matches(['\r', '\n']).to_string();
+            match self.uppercase_keys {
+                true => pairs.push((name.to_uppercase(), value)),
+                false => pairs.push((name, value)),
+            }
+        }
+        pairs.sort();
+        Ok(crate::to_env_string(pairs)?)
+    }
+
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+#[test]
+fn read_secrets_dir() {
+    let dir = std::env::temp_dir().join("simple_env_load_secrets_dir");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("..data")).unwrap();
+    std::fs::write(dir.join("db_password"), "p4$$ word\n").unwrap();
+    std::fs::write(dir.join("tls_cert"), "line 1\nline 2\n").unwrap();
+    std::fs::write(dir.join(".hidden"), "skipped").unwrap();
+
+    let data = SecretsDir::new(&dir).uppercase_keys(true).read().unwrap();
+    assert_eq!(
+        crate::parse_to_map(&data),
+        [
+            ("DB_PASSWORD".into(), "p4$$ word".into()),
+            ("TLS_CERT".into(), "line 1\nline 2".into())
+        ]
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    let err = SecretsDir::new(&dir).read().unwrap_err();
+    assert_eq!(err.kind(), Some(std::io::ErrorKind::NotFound));
+}
@@ -0,0 +1,57 @@
+use std::sync::mpsc::{self, Receiver};
+
+use signal_hook::{consts::SIGHUP, iterator::Signals};
+
+use crate::{Changes, LoadError, Loader};
+
+/// See [`Loader::reload_on_sighup`]
+pub(crate) fn reload_on_sighup(
+    loader: Loader,
+) -> std::io::Result<Receiver<Result<Changes, LoadError>>> {
+    let mut signals = Signals::new([SIGHUP])?;
+    let mut current = loader.evaluate_all().unwrap_or_default();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            let changes = loader.evaluate_all().map(|report| {
+                let changes = Changes::between(&current, &report);
+                current = report;
+                changes
+            });
+            if let Ok(changes) = &changes {
+                for var in changes.added.iter().chain(&changes.changed) {
+                    std::env::set_var(&var.key, &var.value);
+                }
+                changes
+                    .removed
+                    .iter()
+                    .for_each(|key| std::env::remove_var(key));
+            }
+            if tx.send(changes).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(rx)
+}
+
+#[test]
+fn reload_on_signal() {
+    use std::time::Duration;
+
+    let path = std::env::temp_dir().join("simple_env_load_sighup.env");
+    std::fs::write(&path, "SIMPLE_ENV_LOAD_SIGHUP=1").unwrap();
+    let loader = Loader::new().path(&path);
+    loader.load().unwrap();
+    let reloads = loader.reload_on_sighup().unwrap();
+
+    std::fs::write(&path, "SIMPLE_ENV_LOAD_SIGHUP=2").unwrap();
+    signal_hook::low_level::raise(SIGHUP).unwrap();
+    let changes = reloads
+        .recv_timeout(Duration::from_secs(5))
+        .unwrap()
+        .unwrap();
+    assert_eq!(changes.changed[0].key, "SIMPLE_ENV_LOAD_SIGHUP");
+    assert_eq!(std::env::var("SIMPLE_ENV_LOAD_SIGHUP").unwrap(), "2");
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,48 @@
+use std::{ffi::OsStr, path::Path};
+
+/// Whether `data` is a dotenv file encrypted by SOPS, which adds `sops_version` and `sops_mac` metadata keys
+pub(crate) fn is_encrypted(data: &str) -> bool {
+    let keys = data
+        .lines()
+        .filter_map(|line| Some(line.split_once('=')?.0.trim()))
+        .collect::<Vec<_>>();
+    keys.contains(&"sops_version") && keys.contains(&"sops_mac")
+}
+
+/// Decrypts the SOPS encrypted file at `path` with the `sops` binary, see [`Loader::sops`](crate::Loader::sops)
+pub(crate) fn decrypt(path: &Path) -> Result<String, String> {
+    let args = [
+        "--decrypt",
+        "--input-type",
+        "dotenv",
+        "--output-type",
+        "dotenv",
+    ];
+    let args = args.map(OsStr::new).into_iter().chain([path.as_os_str()]);
+    crate::command::run("sops", &args.collect::<Vec<_>>())
+        .map_err(|err| format!("'sops --decrypt' failed: {err}"))
+}
+
+#[test]
+fn sops_files() {
+    let encrypted = "\
+DB_PASSWORD=ENC[AES256_GCM,data:Tr7o1g==,iv:1=,tag:2=,type:str]
+sops_age__list_0__map_recipient=age1qqq
+sops_lastmodified=2024-01-01T00:00:00Z
+sops_mac=ENC[AES256_GCM,data:3=,iv:4=,tag:5=,type:str]
+sops_version=3.8.1
+";
+    assert!(is_encrypted(encrypted));
+    assert!(!is_encrypted("DB_PASSWORD=hunter2\nsops_version=3.8.1"));
+
+    let path = std::env::temp_dir().join("simple_env_load_sops.env");
+    std::fs::write(&path, encrypted).unwrap();
+    // either `sops` isn't installed, or it can't decrypt the made up data
+    let err = crate::Loader::new()
+        .path(&path)
+        .sops(true)
+        .load()
+        .unwrap_err();
+    assert!(matches!(err, crate::LoadError::Decrypt { .. }));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,289 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::limits::read_to_string_limited;
+
+/// Somewhere env data can be read from, see [`Loader::source`](crate::Loader::source)
+///
+/// It's implemented for paths (the file is read), strings (they are the data), [`Reader`]s, [`SecretsDir`](crate::SecretsDir)s
+/// and [`Remote`](crate::Remote)s with the `http` feature. Implement it to load from anywhere else.
+///
+/// ```rust
+/// use simple_env_load::{Loader, Source, SourceError};
+///
+/// struct Vault {
+///     path: String,
+/// }
+///
+/// impl Source for Vault {
+///     fn read(&self) -> Result<String, SourceError> {
+///         match &*self.path {
+///             "secret/app" => Ok("SOURCE_DOC_TOKEN=hunter2".to_string()),
+///             path => Err(SourceError::new(format!("no secret at '{path}'"))),
+///         }
+///     }
+///
+///     fn name(&self) -> String {
+///         format!("vault:{}", self.path)
+///     }
+/// }
+///
+/// let report = Loader::new()
+///     .source("SOURCE_DOC_TOKEN=changeme\nSOURCE_DOC_USER=app")
+///     .source(Vault { path: "secret/app".into() })
+///     .load()
+///     .unwrap();
+///
+/// assert_eq!(std::env::var("SOURCE_DOC_TOKEN").unwrap(), "hunter2");
+/// assert_eq!(report.vars().last().unwrap().origin.to_string(), "vault:secret/app:1");
+/// ```
+pub trait Source {
+    /// Read all of the env data
+    fn read(&self) -> Result<String, SourceError>;
+
+    /// Read the env data, stopping after `limit` bytes, see [`Limits::max_file_size`](crate::Limits::max_file_size)
+    ///
+    /// Data that's cut short is rejected, so it only has to be longer than the limit. By default this is [`read`](Self::read).
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        let _ = limit;
+        self.read()
+    }
+
+    /// What to call this source in errors and [`Origin`](crate::Origin)s (default: `<source>`)
+    fn name(&self) -> String {
+        "<source>".to_string()
+    }
+}
+
+/// An error produced by a [`Source`]
+#[derive(Debug)]
+pub struct SourceError(Box<dyn std::error::Error + Send + Sync>);
+
+impl SourceError {
+    /// Wrap an error, or a message
+    pub fn new(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self(error.into())
+    }
+
+    /// The kind of io error, if this was caused by one
+    pub fn kind(&self) -> Option<std::io::ErrorKind> {
+        self.0
+            .downcast_ref::<std::io::Error>()
+            .map(std::io::Error::kind)
+    }
+}
+
+impl From<std::io::Error> for SourceError {
+    fn from(err: std::io::Error) -> Self {
+        Self::new(err)
+    }
+}
+
+impl std::fmt::Display for SourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for SourceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+/// Env data read from an [`std::io::Read`], e.g. stdin
+///
+/// The reader is read to its end the first time, later reads get whatever is left.
+///
+/// ```rust,no_run
+/// use simple_env_load::{Loader, Reader};
+///
+/// Loader::new()
+///     .source(Reader::new(std::io::stdin()))
+///     .load()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Reader<R>(Mutex<R>);
+
+impl<R: Read> Reader<R> {
+    /// Read env data from `reader`
+    pub fn new(reader: R) -> Self {
+        Self(Mutex::new(reader))
+    }
+}
+
+impl<R: Read> Source for Reader<R> {
+    fn read(&self) -> Result<String, SourceError> {
+        let mut data = String::new();
+        let mut reader = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        reader.read_to_string(&mut data)?;
+        Ok(data)
+    }
+
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        let mut reader = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        Ok(read_to_string_limited(&mut *reader, Some(limit))?)
+    }
+
+    fn name(&self) -> String {
+        "<reader>".to_string()
+    }
+}
+
+impl Source for Path {
+    fn read(&self) -> Result<String, SourceError> {
+        Ok(std::fs::read_to_string(crate::path::expand(self))?)
+    }
+
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        let file = std::fs::File::open(crate::path::expand(self))?;
+        Ok(read_to_string_limited(file, Some(limit))?)
+    }
+
+    fn name(&self) -> String {
+        self.display().to_string()
+    }
+}
+
+impl Source for PathBuf {
+    fn read(&self) -> Result<String, SourceError> {
+        self.as_path().read()
+    }
+
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        self.as_path().read_limited(limit)
+    }
+
+    fn name(&self) -> String {
+        self.as_path().name()
+    }
+}
+
+impl Source for str {
+    fn read(&self) -> Result<String, SourceError> {
+        Ok(self.to_string())
+    }
+
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        Ok(read_to_string_limited(self.as_bytes(), Some(limit))?)
+    }
+
+    fn name(&self) -> String {
+        "<string>".to_string()
+    }
+}
+
+impl Source for String {
+    fn read(&self) -> Result<String, SourceError> {
+        self.as_str().read()
+    }
+
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        self.as_str().read_limited(limit)
+    }
+
+    fn name(&self) -> String {
+        self.as_str().name()
+    }
+}
+
+impl<S: Source + ?Sized> Source for &S {
+    fn read(&self) -> Result<String, SourceError> {
+        (**self).read()
+    }
+
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        (**self).read_limited(limit)
+    }
+
+    fn name(&self) -> String {
+        (**self).name()
+    }
+}
+
+impl<S: Source + ?Sized> Source for Box<S> {
+    fn read(&self) -> Result<String, SourceError> {
+        (**self).read()
+    }
+
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        (**self).read_limited(limit)
+    }
+
+    fn name(&self) -> String {
+        (**self).name()
+    }
+}
+
+impl<S: Source + ?Sized> Source for Arc<S> {
+    fn read(&self) -> Result<String, SourceError> {
+        (**self).read()
+    }
+
+    fn read_limited(&self, limit: u64) -> Result<String, SourceError> {
+        (**self).read_limited(limit)
+    }
+
+    fn name(&self) -> String {
+        (**self).name()
+    }
+}
+
+/// The fields of a JSON object as env data, strings are used as is and other values as JSON
+#[cfg(any(feature = "aws", feature = "vault"))]
+pub(crate) fn json_fields(
+    fields: serde_json::Map<String, serde_json::Value>,
+) -> Result<String, SourceError> {
+    let pairs = fields.into_iter().map(|(key, value)| match value {
+        serde_json::Value::String(value) => (key, value),
+        value => (key, value.to_string()),
+    });
+    Ok(crate::to_env_string(pairs)?)
+}
+
+#[test]
+fn read_sources() {
+    let path = std::env::temp_dir().join("simple_env_load_sources.env");
+    std::fs::write(
+        &path,
+        "SIMPLE_ENV_LOAD_SOURCES_A=file\nSIMPLE_ENV_LOAD_SOURCES_B=file",
+    )
+    .unwrap();
+
+    let sources: Vec<Box<dyn Source + Send + Sync>> = vec![
+        Box::new(path.clone()),
+        Box::new(Reader::new(&b"SIMPLE_ENV_LOAD_SOURCES_B=reader"[..])),
+    ];
+    let report = crate::Loader::new()
+        .sources(sources)
+        .source("SIMPLE_ENV_LOAD_SOURCES_C=${SIMPLE_ENV_LOAD_SOURCES_A}")
+        .load()
+        .unwrap();
+    let origins = report
+        .vars()
+        .iter()
+        .map(|var| (&*var.value, var.origin.to_string()))
+        .collect::<Vec<_>>();
+    let file = path.display();
+    assert_eq!(
+        origins,
+        [
+            ("file", format!("{file}:1")),
+            ("file", format!("{file}:2")),
+            ("reader", "<reader>:1".to_string()),
+            ("file", "<string>:1".to_string()),
+        ]
+    );
+
+    std::fs::remove_file(&path).unwrap();
+    let err = crate::Loader::new()
+        .source(path.clone())
+        .load()
+        .unwrap_err();
+    assert!(matches!(&err, crate::LoadError::Source { name, .. } if *name == file.to_string()));
+    assert_eq!(err.kind(), Some(std::io::ErrorKind::NotFound));
+}
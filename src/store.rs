@@ -0,0 +1,78 @@
+//! A thread-safe store of variables, kept by this crate instead of the process environment
+//!
+//! [`std::env::set_var`] isn't safe when other threads read the environment at the same time,
+//! which is why it's `unsafe` in the 2024 edition. Loading into the store with
+//! [`Backend::Store`](crate::Backend::Store) avoids it, the variables are then read with [`get`].
+//!
+//! ```rust
+//! use simple_env_load::{store, Backend, Loader};
+//!
+//! Loader::new()
+//!     .source("STORE_DOC_PORT=8080")
+//!     .backend(Backend::Store)
+//!     .load()
+//!     .unwrap();
+//!
+//! assert_eq!(store::get("STORE_DOC_PORT").as_deref(), Some("8080"));
+//! assert!(std::env::var("STORE_DOC_PORT").is_err());
+//! assert_eq!(store::env().get::<u16>("STORE_DOC_PORT").unwrap(), 8080);
+//! ```
+
+use std::{
+    collections::HashMap,
+    sync::{OnceLock, RwLock},
+};
+
+use crate::Env;
+
+fn store() -> &'static RwLock<HashMap<String, String>> {
+    static STORE: OnceLock<RwLock<HashMap<String, String>>> = OnceLock::new();
+    STORE.get_or_init(Default::default)
+}
+
+/// The value of a key in the store, or in the process environment if it isn't in the store
+pub fn get(key: &str) -> Option<String> {
+    let vars = store().read().unwrap_or_else(|err| err.into_inner());
+    vars.get(key).cloned().or_else(|| std::env::var(key).ok())
+}
+
+/// Every variable of the process environment, with the ones in the store taking precedence
+pub fn vars() -> Vec<(String, String)> {
+    let mut vars = std::env::vars().collect::<HashMap<_, _>>();
+    let store = store().read().unwrap_or_else(|err| err.into_inner());
+    vars.extend(store.iter().map(|(k, v)| (k.clone(), v.clone())));
+    let mut vars = vars.into_iter().collect::<Vec<_>>();
+    vars.sort();
+    vars
+}
+
+/// Every variable, like [`vars`], with typed accessors
+pub fn env() -> Env {
+    vars().into_iter().collect()
+}
+
+/// Set a key in the store
+pub fn set(key: impl Into<String>, value: impl Into<String>) {
+    let mut vars = store().write().unwrap_or_else(|err| err.into_inner());
+    vars.insert(key.into(), value.into());
+}
+
+/// Remove a key from the store, producing its value
+pub fn remove(key: &str) -> Option<String> {
+    let mut vars = store().write().unwrap_or_else(|err| err.into_inner());
+    vars.remove(key)
+}
+
+#[test]
+fn store_overlays_env() {
+    std::env::set_var("SIMPLE_ENV_LOAD_STORE", "process");
+    assert_eq!(get("SIMPLE_ENV_LOAD_STORE").as_deref(), Some("process"));
+
+    set("SIMPLE_ENV_LOAD_STORE", "store");
+    assert_eq!(get("SIMPLE_ENV_LOAD_STORE").as_deref(), Some("store"));
+    assert!(vars().contains(&("SIMPLE_ENV_LOAD_STORE".into(), "store".into())));
+    assert_eq!(std::env::var("SIMPLE_ENV_LOAD_STORE").unwrap(), "process");
+
+    assert_eq!(remove("SIMPLE_ENV_LOAD_STORE").as_deref(), Some("store"));
+    assert_eq!(get("SIMPLE_ENV_LOAD_STORE").as_deref(), Some("process"));
+}
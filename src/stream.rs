@@ -0,0 +1,102 @@
+use std::io::{self, BufRead};
+
+use crate::{
+    parse::{self, Cursor},
+    Entry,
+};
+
+/// Parse env data as it's read, calling a function for each entry
+///
+/// Only the logical line being parsed is kept in memory, so this works for data of any size,
+/// such as the output of another process. Like [`parse_iter`](crate::parse_iter), values are returned as written,
+/// without [interpolation](crate#interpolation), and lines that can't be parsed are skipped.
+/// Spans are offsets into all of the data that was read.
+///
+/// This fails if reading fails or the data isn't UTF-8, after calling `f` for the entries before it.
+///
+/// ```rust
+/// let data = "HOST=localhost\nCERT=\"line one\nline two\"\nPORT=8080\n";
+/// let mut entries = vec![];
+/// simple_env_load::parse_reader(data.as_bytes(), |entry| {
+///     entries.push((entry.key.to_string(), entry.value.into_owned(), entry.line))
+/// })
+/// .unwrap();
+///
+/// assert_eq!(entries[1], ("CERT".into(), "line one\nline two".into(), 2));
+/// assert_eq!(entries[2], ("PORT".into(), "8080".into(), 4));
+/// ```
+pub fn parse_reader<R: BufRead>(mut reader: R, mut f: impl FnMut(Entry<'_>)) -> io::Result<()> {
+    let options = Default::default();
+    let (mut cursor, mut buf) = (Cursor::new(options, vec![]), String::new());
+    loop {
+        let read = reader.read_line(&mut buf)?;
+        // a quoted value can span lines, so keep reading until it's closed
+        if read != 0 && parse::is_open(&buf, options) {
+            continue;
+        }
+
+        let mut rest = buf.as_str();
+        while !rest.is_empty() {
+            let (_, entry, tail) = cursor.next(&buf, rest);
+            if let Ok(Some(entry)) = entry {
+                f(entry)
+            }
+            rest = tail;
+        }
+        cursor.offset += buf.len();
+        buf.clear();
+
+        if read == 0 {
+            return Ok(());
+        }
+    }
+}
+
+#[test]
+fn parse_streamed() {
+    let data = "\
+# a comment
+HOST=localhost
+CERT=\"-----BEGIN-----
+abc
+-----END-----\" # the cert
+#if os=none
+SKIPPED=1
+#endif
+export PORT = 8080
+BROKEN
+NAME='unterminated
+LAST=1";
+
+    let expected = crate::parse_iter(data)
+        .map(|entry| {
+            (
+                entry.key.to_string(),
+                entry.value.into_owned(),
+                entry.line,
+                entry.span,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(expected.len(), 4);
+
+    let reader = io::BufReader::with_capacity(1, data.as_bytes());
+    let mut entries = vec![];
+    parse_reader(reader, |entry| {
+        entries.push((
+            entry.key.to_string(),
+            entry.value.into_owned(),
+            entry.line,
+            entry.span,
+        ))
+    })
+    .unwrap();
+    assert_eq!(entries, expected);
+
+    let mut keys = vec![];
+    let err = parse_reader(&b"A=1\nB=\xff\nC=3"[..], |entry| {
+        keys.push(entry.key.to_string())
+    });
+    assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    assert_eq!(keys, ["A"]);
+}
@@ -0,0 +1,172 @@
+use std::path::Path;
+
+type Pairs = Vec<(String, String)>;
+
+/// Converts a `.json`, `.yaml`, `.yml` or `.toml` file to env data, other files are returned as is
+///
+/// The file is an object of variables, nested objects and arrays are flattened by joining their keys
+/// with `__`, e.g. `{"db": {"hosts": ["a"]}}` is `db__hosts__0=a`. Strings are used as is, `null` is
+/// empty and other values are written as they are in the file. For TOML, only the `table` is read,
+/// when there is one.
+#[cfg_attr(not(feature = "toml"), allow(unused_variables))]
+pub(crate) fn convert(path: &Path, data: String, table: Option<&str>) -> std::io::Result<String> {
+    let invalid = |err: String| std::io::Error::new(std::io::ErrorKind::InvalidData, err);
+    let mut pairs = vec![];
+    match path.extension().and_then(|ext| ext.to_str()) {
+        #[cfg(feature = "json")]
+        Some("json") => match serde_json::from_str(&data)? {
+            serde_json::Value::Object(fields) => {
+                fields
+                    .into_iter()
+                    .for_each(|(key, value)| flatten_json(key, value, &mut pairs));
+            }
+            _ => return Err(invalid("expected an object of variables".into())),
+        },
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => {
+            match serde_yaml::from_str(&data).map_err(|err| invalid(err.to_string()))? {
+                serde_yaml::Value::Mapping(fields) => {
+                    for (key, value) in fields {
+                        flatten_yaml(yaml_key(key)?, value, &mut pairs)?
+                    }
+                }
+                serde_yaml::Value::Null => {}
+                _ => return Err(invalid("expected a mapping of variables".into())),
+            }
+        }
+        #[cfg(feature = "toml")]
+        Some("toml") => {
+            let mut fields =
+                toml::from_str::<toml::Table>(&data).map_err(|err| invalid(err.to_string()))?;
+            for name in table.into_iter().flat_map(|table| table.split('.')) {
+                fields = match fields.remove(name) {
+                    Some(toml::Value::Table(table)) => table,
+                    Some(..) => return Err(invalid(format!("'{name}' isn't a table"))),
+                    None => toml::Table::new(),
+                };
+            }
+            fields
+                .into_iter()
+                .for_each(|(key, value)| flatten_toml(key, value, &mut pairs));
+        }
+        _ => return Ok(data),
+    }
+    crate::to_env_string(pairs)
+}
+
+#[cfg(feature = "json")]
+fn flatten_json(key: String, value: serde_json::Value, pairs: &mut Pairs) {
+    use serde_json::Value;
+    match value {
+        Value::Object(fields) => fields
+            .into_iter()
+            .for_each(|(name, value)| flatten_json(format!("{key}__{name}"), value, pairs)),
+        Value::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .for_each(|(i, value)| flatten_json(format!("{key}__{i}"), value, pairs)),
+        Value::String(value) => pairs.push((key, value)),
+        Value::Null => pairs.push((key, String::new())),
+        value => pairs.push((key, value.to_string())),
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn flatten_yaml(key: String, value: serde_yaml::Value, pairs: &mut Pairs) -> std::io::Result<()> {
+    use serde_yaml::Value;
+    match value {
+        Value::Mapping(fields) => {
+            for (name, value) in fields {
+                flatten_yaml(format!("{key}__{}", yaml_key(name)?), value, pairs)?
+            }
+        }
+        Value::Sequence(items) => {
+            for (i, value) in items.into_iter().enumerate() {
+                flatten_yaml(format!("{key}__{i}"), value, pairs)?
+            }
+        }
+        Value::Tagged(tagged) => flatten_yaml(key, tagged.value, pairs)?,
+        Value::String(value) => pairs.push((key, value)),
+        Value::Number(value) => pairs.push((key, value.to_string())),
+        Value::Bool(value) => pairs.push((key, value.to_string())),
+        Value::Null => pairs.push((key, String::new())),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "toml")]
+fn flatten_toml(key: String, value: toml::Value, pairs: &mut Pairs) {
+    use toml::Value;
+    match value {
+        Value::Table(fields) => fields
+            .into_iter()
+            .for_each(|(name, value)| flatten_toml(format!("{key}__{name}"), value, pairs)),
+        Value::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .for_each(|(i, value)| flatten_toml(format!("{key}__{i}"), value, pairs)),
+        Value::String(value) => pairs.push((key, value)),
+        value => pairs.push((key, value.to_string())),
+    }
+}
+
+/// Mappings can have any scalar as a key, e.g. `8080: http`
+#[cfg(feature = "yaml")]
+fn yaml_key(key: serde_yaml::Value) -> std::io::Result<String> {
+    use serde_yaml::Value;
+    match key {
+        Value::String(key) => Ok(key),
+        Value::Number(key) => Ok(key.to_string()),
+        Value::Bool(key) => Ok(key.to_string()),
+        key => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{key:?} can't be a variable name"),
+        )),
+    }
+}
+
+#[test]
+fn convert_structured_files() {
+    let convert = |path: &str, data: &str| convert(Path::new(path), data.into(), None);
+    assert_eq!(convert(".env", "A=1").unwrap(), "A=1");
+
+    #[cfg(feature = "json")]
+    {
+        let data = r#"{"A": "a b", "B": 1, "C": null, "DB": {"HOSTS": ["x", "y"], "TLS": true}}"#;
+        let env = convert("config.json", data).unwrap();
+        assert_eq!(
+            env,
+            "A='a b'\nB=1\nC=\nDB__HOSTS__0=x\nDB__HOSTS__1=y\nDB__TLS=true\n"
+        );
+        assert!(convert("config.json", "[1]").is_err());
+        assert!(convert("config.json", "{").is_err());
+    }
+
+    #[cfg(feature = "yaml")]
+    {
+        let data = "A: a b\nB: 1\nC:\nDB:\n  HOSTS: [x, y]\n  TLS: true\n";
+        let env = convert("config.yaml", data).unwrap();
+        assert_eq!(
+            env,
+            "A='a b'\nB=1\nC=\nDB__HOSTS__0=x\nDB__HOSTS__1=y\nDB__TLS=true\n"
+        );
+        assert_eq!(convert("empty.yml", "").unwrap(), "");
+        assert!(convert("config.yml", "- 1").is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    {
+        let data = "A = 'a b'\nB = 1\n[DB]\nHOSTS = ['x', 'y']\nTLS = true\n";
+        let env = convert("config.toml", data).unwrap();
+        assert_eq!(
+            env,
+            "A='a b'\nB=1\nDB__HOSTS__0=x\nDB__HOSTS__1=y\nDB__TLS=true\n"
+        );
+
+        let data = "name = 'app'\n[tool.app.env]\nPORT = 8080\n";
+        let table = |table| self::convert(Path::new("a.toml"), data.into(), Some(table));
+        assert_eq!(table("tool.app.env").unwrap(), "PORT=8080\n");
+        assert_eq!(table("missing").unwrap(), "");
+        assert!(table("name").is_err());
+    }
+}
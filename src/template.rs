@@ -0,0 +1,115 @@
+use crate::{
+    interpolate::{self, ExpandError, Ref},
+    Env,
+};
+
+/// What [`render_with`] does with a `${KEY}` of a key that isn't set
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnsetPolicy {
+    /// The placeholder is replaced with nothing
+    #[default]
+    Empty,
+    /// The placeholder is kept as it was written
+    Keep,
+    /// Fail with [`ExpandError::Unset`]
+    Error,
+}
+
+/// Renders a template, such as an `nginx.conf.tmpl`, replacing its `${KEY}` placeholders with the values of `env`
+///
+/// `${KEY:-default}` and `${KEY:?message}` act like they do in env files, see the [crate docs](crate#interpolation).
+/// Only braced placeholders are replaced, so `$host` is kept as is, and `$${` is a literal `${`.
+/// Placeholders of keys that aren't set are replaced with nothing, see [`render_with`].
+///
+/// ```rust
+/// use simple_env_load::Env;
+///
+/// let env = "SERVER_NAME=example.com\nPORT=8080".parse::<Env>().unwrap();
+/// let template = "server_name ${SERVER_NAME};\nlisten ${PORT};\nroot ${ROOT:-/var/www};\nproxy_set_header Host $host;";
+///
+/// assert_eq!(
+///     simple_env_load::render(template, &env).unwrap(),
+///     "server_name example.com;\nlisten 8080;\nroot /var/www;\nproxy_set_header Host $host;"
+/// );
+/// ```
+pub fn render(template: &str, env: &Env) -> Result<String, ExpandError> {
+    render_with(template, env, UnsetPolicy::Empty)
+}
+
+/// Like [`render`], choosing what happens to the placeholders of keys that aren't set
+///
+/// A placeholder with a default or a message is never affected by `unset`.
+///
+/// ```rust
+/// use simple_env_load::{Env, UnsetPolicy};
+///
+/// let env = Env::default();
+/// let out = simple_env_load::render_with("${A} ${B:-b}", &env, UnsetPolicy::Keep).unwrap();
+/// assert_eq!(out, "${A} b");
+///
+/// let err = simple_env_load::render_with("${A}", &env, UnsetPolicy::Error).unwrap_err();
+/// assert_eq!(err.to_string(), "'A' is not set");
+/// ```
+pub fn render_with(template: &str, env: &Env, unset: UnsetPolicy) -> Result<String, ExpandError> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(pos) = rest.find("${") {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos + 1..];
+        if rest[..pos].ends_with('$') {
+            out.push('{');
+            rest = &tail[1..];
+            continue;
+        }
+
+        let Some(reference) = interpolate::reference(tail) else {
+            out.push_str("${");
+            rest = &tail[1..];
+            continue;
+        };
+        let placeholder = &rest[pos..pos + 1 + reference.len];
+        rest = &tail[reference.len..];
+
+        if reference.modifier.is_none() && env.get_str(reference.name).is_none() {
+            match unset {
+                UnsetPolicy::Empty => {}
+                UnsetPolicy::Keep => out.push_str(placeholder),
+                UnsetPolicy::Error => {
+                    return Err(ExpandError::Unset {
+                        key: reference.name.to_string(),
+                        message: String::new(),
+                    })
+                }
+            }
+            continue;
+        }
+
+        let lookup = |reference: Ref<'_>| match reference {
+            Ref::Var(name) => Ok::<_, ExpandError>(env.get_str(name).map(ToString::to_string)),
+            Ref::Command(..) => Ok(None),
+        };
+        out.push_str(&interpolate::expand(placeholder, false, false, lookup)?);
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[test]
+fn render_templates() {
+    let env = [("A", "1"), ("EMPTY", "")].into_iter().collect::<Env>();
+    let render = |template| render_with(template, &env, UnsetPolicy::Keep);
+
+    assert_eq!(render("${A}${A}").unwrap(), "11");
+    assert_eq!(render("$A ${A} $${A} ${").unwrap(), "$A 1 ${A} ${");
+    assert_eq!(
+        render("${B} ${B-x} ${EMPTY:-y} ${EMPTY-z}").unwrap(),
+        "${B} x y "
+    );
+    assert_eq!(render("${B:-${A}} ${B:-$(id)}").unwrap(), "1 $(id)");
+    assert_eq!(
+        render("${B:?needed by nginx}").unwrap_err().to_string(),
+        "'B' is not set: needed by nginx"
+    );
+    assert_eq!(render_with("${B}", &env, UnsetPolicy::Empty).unwrap(), "");
+}
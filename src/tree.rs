@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+/// Variables grouped by the parts of their keys, e.g. `DB__HOST` is the `HOST` child of `DB`
+///
+/// This is how hierarchical config, such as a [JSON or TOML file](crate::Loader::path), is written as
+/// variables. [`Tree::from_pairs`] groups the variables, and [`Tree::flatten`] turns a tree back into them.
+/// A key can have both a value and children, e.g. `DB=postgres` and `DB__HOST=localhost`.
+///
+/// ```rust
+/// use simple_env_load::Tree;
+///
+/// let vars = simple_env_load::parse_to_map("DB__HOST=localhost\nDB__PORT=5432\nNAME=app");
+/// let tree = Tree::from_pairs(vars);
+///
+/// let db = tree.get("DB").unwrap();
+/// assert_eq!(db.get("PORT").unwrap().value.as_deref(), Some("5432"));
+/// assert_eq!(db.value, None);
+///
+/// assert_eq!(
+///     tree.flatten(),
+///     [
+///         ("DB__HOST".to_string(), "localhost".to_string()),
+///         ("DB__PORT".to_string(), "5432".to_string()),
+///         ("NAME".to_string(), "app".to_string()),
+///     ]
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tree {
+    /// The value of the key itself
+    pub value: Option<String>,
+    /// The keys nested under this one, by the part of their key after the separator
+    pub children: BTreeMap<String, Tree>,
+}
+
+impl Tree {
+    /// The separator [`Tree::from_pairs`] and [`Tree::flatten`] use
+    pub const SEPARATOR: &'static str = "__";
+
+    /// An empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Groups `(key, value)` pairs by [`Tree::SEPARATOR`], later pairs replace earlier ones
+    pub fn from_pairs<I, K, V>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        Self::from_pairs_with(pairs, Self::SEPARATOR)
+    }
+
+    /// Groups `(key, value)` pairs by `separator`, later pairs replace earlier ones
+    pub fn from_pairs_with<I, K, V>(pairs: I, separator: &str) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let mut tree = Self::new();
+        for (key, value) in pairs {
+            tree.insert_with(key.as_ref(), separator, value);
+        }
+        tree
+    }
+
+    /// Set the value of `key`, split by `separator`, creating the keys above it
+    pub fn insert_with(&mut self, key: &str, separator: &str, value: impl Into<String>) {
+        let node = key.split(separator).fold(self, |node, part| {
+            node.children.entry(part.to_string()).or_default()
+        });
+        node.value = Some(value.into());
+    }
+
+    /// The child for a part of a key
+    pub fn get(&self, part: &str) -> Option<&Self> {
+        self.children.get(part)
+    }
+
+    /// The variables of the tree, with their keys joined by [`Tree::SEPARATOR`], sorted by key
+    pub fn flatten(&self) -> Vec<(String, String)> {
+        self.flatten_with(Self::SEPARATOR)
+    }
+
+    /// The variables of the tree, with their keys joined by `separator`, sorted by key
+    ///
+    /// The value of the tree itself has no key, so it isn't included.
+    pub fn flatten_with(&self, separator: &str) -> Vec<(String, String)> {
+        fn walk(tree: &Tree, key: &str, separator: &str, out: &mut Vec<(String, String)>) {
+            if let Some(value) = &tree.value {
+                out.push((key.to_string(), value.clone()));
+            }
+            for (part, child) in &tree.children {
+                let key = match key {
+                    "" => part.clone(),
+                    key => format!("{key}{separator}{part}"),
+                };
+                walk(child, &key, separator, out)
+            }
+        }
+
+        let mut out = vec![];
+        for (part, child) in &self.children {
+            walk(child, part, separator, &mut out)
+        }
+        out
+    }
+}
+
+#[test]
+fn tree_round_trip() {
+    let pairs = [
+        ("APP", "demo"),
+        ("APP__DB__HOST", "localhost"),
+        ("APP__DB__PORT", "5432"),
+        ("APP__NAME", "first"),
+        ("APP__NAME", "second"),
+    ];
+    let tree = Tree::from_pairs(pairs);
+    let app = tree.get("APP").unwrap();
+    assert_eq!(app.value.as_deref(), Some("demo"));
+    assert_eq!(app.get("NAME").unwrap().value.as_deref(), Some("second"));
+    assert_eq!(app.children.keys().collect::<Vec<_>>(), ["DB", "NAME"]);
+
+    let flat = tree.flatten();
+    let expected = [
+        ("APP", "demo"),
+        ("APP__DB__HOST", "localhost"),
+        ("APP__DB__PORT", "5432"),
+        ("APP__NAME", "second"),
+    ];
+    assert_eq!(flat, expected.map(|(k, v)| (k.to_string(), v.to_string())));
+    assert_eq!(Tree::from_pairs(flat), tree);
+
+    let tree = Tree::from_pairs_with([("a.b", "1"), ("a.c", "2")], ".");
+    assert_eq!(
+        tree.flatten_with(":"),
+        [("a:b".into(), "1".into()), ("a:c".into(), "2".into())]
+    );
+    assert!(Tree::new().flatten().is_empty());
+}
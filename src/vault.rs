@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use crate::{Remote, Source, SourceError};
+
+/// A secret in a HashiCorp Vault KV version 2 engine, see [`Loader::source`](crate::Loader::source)
+///
+/// Each field of the secret is a key. It's fetched like a [`Remote`], with the token sent as a bearer token,
+/// so the secret is never written to disk. Values are taken literally, they aren't expanded.
+///
+/// ```rust,no_run
+/// use simple_env_load::{Loader, VaultSecret};
+///
+/// let token = std::env::var("VAULT_TOKEN").unwrap();
+/// Loader::new()
+///     .path(".env")
+///     .source(VaultSecret::new("https://vault.example.com:8200", token, "app/prod"))
+///     .load()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct VaultSecret {
+    address: String,
+    path: String,
+    mount: String,
+    remote: Remote,
+}
+
+impl VaultSecret {
+    /// Read the secret at `path` from the server at `address`, using `token`
+    pub fn new(
+        address: impl Into<String>,
+        token: impl Into<String>,
+        path: impl Into<String>,
+    ) -> Self {
+        let (address, path) = (address.into(), path.into());
+        Self {
+            remote: Remote::new("").bearer_token(token),
+            address: address.trim_end_matches('/').to_string(),
+            path: path.trim_matches('/').to_string(),
+            mount: "secret".to_string(),
+        }
+    }
+
+    /// Read the secret at `path`, with the address and token from `VAULT_ADDR` and `VAULT_TOKEN`
+    ///
+    /// Like the `vault` CLI, the address defaults to `https://127.0.0.1:8200`.
+    pub fn from_env(path: impl Into<String>) -> Self {
+        let address = std::env::var("VAULT_ADDR");
+        Self::new(
+            address.as_deref().unwrap_or("https://127.0.0.1:8200"),
+            std::env::var("VAULT_TOKEN").unwrap_or_default(),
+            path,
+        )
+    }
+
+    /// Where the KV engine is mounted (default: `secret`)
+    pub fn mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into().trim_matches('/').to_string();
+        self
+    }
+
+    /// How long each attempt can take, see [`Remote::timeout`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.remote = self.remote.timeout(timeout);
+        self
+    }
+
+    /// How many times a failed request is retried, see [`Remote::retries`]
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.remote = self.remote.retries(retries);
+        self
+    }
+
+    fn url(&self) -> String {
+        format!("{}/v1/{}/data/{}", self.address, self.mount, self.path)
+    }
+}
+
+impl Source for VaultSecret {
+    fn read(&self) -> Result<String, SourceError> {
+        let mut remote = self.remote.clone();
+        remote.url = self.url();
+        fields(&remote.get(None).map_err(SourceError::new)?)
+    }
+
+    fn name(&self) -> String {
+        format!("vault:{}/{}", self.mount, self.path)
+    }
+}
+
+/// The fields of the secret in a KV version 2 read response, as env data
+fn fields(response: &str) -> Result<String, SourceError> {
+    let mut response =
+        serde_json::from_str::<serde_json::Value>(response).map_err(SourceError::new)?;
+    match response["data"]["data"].take() {
+        serde_json::Value::Object(fields) => crate::source::json_fields(fields),
+        _ => Err(SourceError::new(
+            "expected the secret's data to be an object",
+        )),
+    }
+}
+
+#[test]
+fn vault_secret() {
+    let secret = VaultSecret::new("http://vault:8200/", "s.token", "/app/prod").mount("kv");
+    assert_eq!(secret.url(), "http://vault:8200/v1/kv/data/app/prod");
+    assert_eq!(secret.name(), "vault:kv/app/prod");
+    assert!(!format!("{secret:?}").contains("s.token"));
+
+    let response =
+        r#"{"data": {"data": {"DB_PASS": "p4$$", "PORT": 5432}, "metadata": {"version": 3}}}"#;
+    let data = fields(response).unwrap();
+    assert_eq!(
+        crate::parse_to_map(&data),
+        [
+            ("DB_PASS".into(), "p4$$".into()),
+            ("PORT".into(), "5432".into())
+        ]
+    );
+    assert!(fields(r#"{"errors": ["permission denied"]}"#).is_err());
+}
@@ -0,0 +1,85 @@
+use std::{
+    collections::HashMap,
+    hash::BuildHasher,
+    path::{Path, PathBuf},
+};
+
+/// Where a [`Loader`](crate::Loader) reads its files from, see [`Loader::vfs`](crate::Loader::vfs)
+///
+/// It's implemented for closures, and for maps of paths to contents, e.g. files embedded with `include_str!`.
+pub trait Vfs {
+    /// The contents of the file at `path`, a missing file is a [`NotFound`](std::io::ErrorKind::NotFound) error
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>>;
+
+    /// The first `limit` bytes of the file at `path`, see [`Limits::max_file_size`](crate::Limits::max_file_size)
+    ///
+    /// By default this is all of [`read`](Self::read).
+    fn read_limited(&self, path: &Path, limit: u64) -> std::io::Result<Vec<u8>> {
+        let _ = limit;
+        self.read(path)
+    }
+}
+
+impl<F> Vfs for F
+where
+    F: Fn(&Path) -> std::io::Result<Vec<u8>>,
+{
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        self(path)
+    }
+}
+
+impl<T, S> Vfs for HashMap<PathBuf, T, S>
+where
+    T: AsRef<[u8]>,
+    S: BuildHasher,
+{
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        match self.get(path) {
+            Some(data) => Ok(data.as_ref().to_vec()),
+            None => Err(std::io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    fn read_limited(&self, path: &Path, limit: u64) -> std::io::Result<Vec<u8>> {
+        match self.get(path) {
+            Some(data) => crate::limits::read_limited(data.as_ref(), Some(limit)),
+            None => Err(std::io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+#[test]
+fn read_from_vfs() {
+    let files = HashMap::from([
+        (
+            PathBuf::from("app/.env"),
+            "#include base.env\nSIMPLE_ENV_LOAD_VFS_A=app\nSIMPLE_ENV_LOAD_VFS_KEY_FILE=key",
+        ),
+        (
+            PathBuf::from("app/base.env"),
+            "SIMPLE_ENV_LOAD_VFS_A=base\nSIMPLE_ENV_LOAD_VFS_B=base",
+        ),
+        (PathBuf::from("app/key"), "secret\n"),
+    ]);
+    crate::Loader::new()
+        .path("app/.env")
+        .optional_path("app/.env.local")
+        .includes(true)
+        .file_references(true)
+        .vfs(files)
+        .backend(crate::Backend::Store)
+        .load()
+        .unwrap();
+    let get = |key| crate::store::get(key).unwrap();
+    assert_eq!(get("SIMPLE_ENV_LOAD_VFS_A"), "app");
+    assert_eq!(get("SIMPLE_ENV_LOAD_VFS_B"), "base");
+    assert_eq!(get("SIMPLE_ENV_LOAD_VFS_KEY"), "secret");
+
+    let err = crate::Loader::new()
+        .path("missing.env")
+        .vfs(|_: &Path| Err(std::io::ErrorKind::PermissionDenied.into()))
+        .load()
+        .unwrap_err();
+    assert_eq!(err.kind(), Some(std::io::ErrorKind::PermissionDenied));
+}
@@ -0,0 +1,101 @@
+use std::{collections::HashSet, path::Path};
+
+use notify::Watcher as _;
+
+use crate::{Changes, LoadError, Loader};
+
+/// Watches the files of a [`Loader`] until it's dropped, see [`Loader::watch`]
+pub struct Watcher {
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl std::fmt::Debug for Watcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Watcher")
+    }
+}
+
+pub(crate) fn watch(
+    loader: Loader,
+    mut callback: impl FnMut(Result<Changes, LoadError>) + Send + 'static,
+) -> Result<Watcher, LoadError> {
+    let report = loader.evaluate_all()?;
+    let files = loader.files()?.into_iter();
+    let files = files
+        .chain(report.vars().iter().map(|var| var.origin.path.clone()))
+        .filter_map(|path| std::path::absolute(path).ok())
+        .collect::<HashSet<_>>();
+    let dirs = files
+        .iter()
+        .filter_map(|path| path.parent())
+        .map(Path::to_path_buf)
+        .collect::<HashSet<_>>();
+
+    let mut current = report;
+    let handler = move |event: notify::Result<notify::Event>| {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => return callback(Err(LoadError::Watch(err))),
+        };
+        if event.kind.is_access() || !event.paths.iter().any(|path| files.contains(path)) {
+            return;
+        }
+
+        // let a file that's being written settle, the events that follow find nothing new
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        match loader.evaluate_all() {
+            Ok(report) => {
+                let changes = Changes::between(&current, &report);
+                current = report;
+                if !changes.is_empty() {
+                    callback(Ok(changes))
+                }
+            }
+            Err(err) => callback(Err(err)),
+        }
+    };
+
+    let mut watcher = notify::recommended_watcher(handler).map_err(LoadError::Watch)?;
+    for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+        watcher
+            .watch(dir, notify::RecursiveMode::NonRecursive)
+            .map_err(LoadError::Watch)?;
+    }
+    Ok(Watcher { _watcher: watcher })
+}
+
+#[test]
+fn watch_files() {
+    use std::time::Duration;
+
+    let dir = std::env::temp_dir().join("simple_env_load_watch");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(".env");
+    std::fs::write(
+        &path,
+        "SIMPLE_ENV_LOAD_WATCH_A=1\nSIMPLE_ENV_LOAD_WATCH_B=1",
+    )
+    .unwrap();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let watcher = Loader::new()
+        .path(&path)
+        .watch(move |changes| tx.send(changes.unwrap()).unwrap())
+        .unwrap();
+
+    // replace the file like an editor would, so there are no half written states
+    let tmp = dir.join(".env.tmp");
+    std::fs::write(&tmp, "SIMPLE_ENV_LOAD_WATCH_A=2\nSIMPLE_ENV_LOAD_WATCH_C=1").unwrap();
+    std::fs::rename(&tmp, &path).unwrap();
+    let changes = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    let keys =
+        |vars: &[crate::LoadedVar]| vars.iter().map(|var| var.key.clone()).collect::<Vec<_>>();
+    assert_eq!(keys(&changes.added), ["SIMPLE_ENV_LOAD_WATCH_C"]);
+    assert_eq!(keys(&changes.changed), ["SIMPLE_ENV_LOAD_WATCH_A"]);
+    assert_eq!(changes.removed, ["SIMPLE_ENV_LOAD_WATCH_B"]);
+    assert!(std::env::var("SIMPLE_ENV_LOAD_WATCH_A").is_err());
+
+    drop(watcher);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,610 @@
+use std::{
+    borrow::Cow,
+    io::Write,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::Dialect;
+
+/// Writes `(key, value)` pairs as an env document, one `KEY=value` per line
+///
+/// Values are quoted only when they need to be:
+/// - plain values are written as is
+/// - values with spaces, `#`, `$`, `"` or `\` are single quoted, so they're taken literally
+/// - values with `'` or control characters (e.g. newlines) are double quoted and escaped
+///
+/// Parsing the output produces the same pairs. A key that is empty, or has whitespace, `=` or `#` in it, is an
+/// [`InvalidInput`](std::io::ErrorKind::InvalidInput) error.
+///
+/// ```rust
+/// let mut out = vec![];
+/// simple_env_load::write_env(&mut out, [("NAME", "app"), ("GREETING", "hello world")]).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "NAME=app\nGREETING='hello world'\n");
+/// ```
+pub fn write_env<W, I, K, V>(mut out: W, pairs: I) -> std::io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    for (key, value) in pairs {
+        let key = key.as_ref();
+        if !valid_key(key) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("'{key}' can't be written as a key"),
+            ));
+        }
+        writeln!(out, "{}={}", key, quote(value.as_ref()))?;
+    }
+    Ok(())
+}
+
+/// Writes `(key, value)` pairs as an env document for the tool `dialect` is for
+///
+/// Like [`write_env`], values are quoted only when they need to be, and parsing the output with the same
+/// [`Dialect`] produces the same pairs. [`Dialect::DockerCompose`] has no quotes, so a value with a line break
+/// is an [`InvalidInput`](std::io::ErrorKind::InvalidInput) error.
+///
+/// ```rust
+/// use simple_env_load::Dialect;
+///
+/// let mut out = vec![];
+/// let pairs = [("NAME", "app"), ("GREETING", "say \"hi\"\nthen $LEAVE")];
+/// simple_env_load::write_env_as(&mut out, Dialect::Systemd, pairs).unwrap();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "NAME=app\nGREETING=\"say \\\"hi\\\"\nthen \\$LEAVE\"\n"
+/// );
+/// ```
+pub fn write_env_as<W, I, K, V>(mut out: W, dialect: Dialect, pairs: I) -> std::io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    if matches!(dialect, Dialect::Dotenv | Dialect::Ruby | Dialect::Compose) {
+        return write_env(out, pairs);
+    }
+
+    for (key, value) in pairs {
+        let (key, value) = (key.as_ref(), value.as_ref());
+        let invalid = |what: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("'{key}' can't be written as a {what}"),
+            )
+        };
+        if !valid_key(key) || key.contains(';') {
+            return Err(invalid("key"));
+        }
+        match dialect {
+            Dialect::Posix if !is_identifier(key) => return Err(invalid("shell variable")),
+            Dialect::Posix => writeln!(out, "{key}={}", quote_posix(value))?,
+            // compose takes values as is, a line break would end the value
+            Dialect::DockerCompose if value.contains(['\n', '\r']) => {
+                return Err(invalid("single line value"))
+            }
+            Dialect::DockerCompose => writeln!(out, "{key}={value}")?,
+            _ => writeln!(out, "{key}={}", quote_systemd(value))?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes `(key, value)` pairs as an env document for `dialect` to a string, see [`write_env_as`]
+pub fn to_env_string_as<I, K, V>(dialect: Dialect, pairs: I) -> std::io::Result<String>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut out = vec![];
+    write_env_as(&mut out, dialect, pairs)?;
+    Ok(String::from_utf8(out).expect("only strings were written"))
+}
+
+/// Writes `(key, value)` pairs as an env document to a string, see [`write_env`]
+///
+/// ```rust
+/// let pairs = [("CERT", "line 1\nline 2"), ("QUOTE", "it's"), ("PRICE", "$5")];
+/// let data = simple_env_load::to_env_string(pairs).unwrap();
+/// assert_eq!(data, "CERT=\"line 1\\nline 2\"\nQUOTE=\"it's\"\nPRICE='$5'\n");
+///
+/// let parsed = simple_env_load::parse_to_map(&data);
+/// assert_eq!(parsed[0], ("CERT".into(), "line 1\nline 2".into()));
+/// assert_eq!(parsed[2], ("PRICE".into(), "$5".into()));
+/// ```
+pub fn to_env_string<I, K, V>(pairs: I) -> std::io::Result<String>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut out = vec![];
+    write_env(&mut out, pairs)?;
+    Ok(String::from_utf8(out).expect("only strings were written"))
+}
+
+/// The shells [`write_shell`] can write scripts for
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Shell {
+    /// `export KEY='value'`, for sh, bash, zsh and other POSIX shells
+    #[default]
+    Posix,
+    /// `set -gx KEY 'value'`
+    Fish,
+    /// `$env:KEY = 'value'`
+    PowerShell,
+    /// A JSON object of keys to values, the format of `direnv dump json`
+    Direnv,
+}
+
+/// Writes `(key, value)` pairs as a script that sets them as environment variables in `shell`
+///
+/// Values are always single quoted in the way `shell` takes them literally, so evaluating the script
+/// can't run anything. For [`Shell::Direnv`], the pairs are written as one JSON object. Keys must be letters, digits and underscores, not starting with a digit,
+/// others are an [`InvalidInput`](std::io::ErrorKind::InvalidInput) error.
+///
+/// ```rust
+/// use simple_env_load::Shell;
+///
+/// let pairs = [("GREETING", "it's $HOME")];
+/// let script = |shell| simple_env_load::to_shell_string(shell, pairs).unwrap();
+///
+/// assert_eq!(script(Shell::Posix), "export GREETING='it'\\''s $HOME'\n");
+/// assert_eq!(script(Shell::Fish), "set -gx GREETING 'it\\'s $HOME'\n");
+/// assert_eq!(script(Shell::PowerShell), "$env:GREETING = 'it''s $HOME'\n");
+/// assert_eq!(script(Shell::Direnv), "{\"GREETING\":\"it's $HOME\"}\n");
+/// ```
+pub fn write_shell<W, I, K, V>(mut out: W, shell: Shell, pairs: I) -> std::io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut first = true;
+    if shell == Shell::Direnv {
+        write!(out, "{{")?;
+    }
+    for (key, value) in pairs {
+        let (key, value) = (key.as_ref(), value.as_ref());
+        if !is_identifier(key) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("'{key}' can't be written as a shell variable"),
+            ));
+        }
+        match shell {
+            Shell::Posix => writeln!(out, "export {key}='{}'", value.replace('\'', r"'\''"))?,
+            Shell::Fish => {
+                let value = value.replace('\\', r"\\").replace('\'', r"\'");
+                writeln!(out, "set -gx {key} '{value}'")?
+            }
+            Shell::PowerShell => {
+                // powershell also takes the typographic single quotes as quotes
+                let mut quoted = String::with_capacity(value.len());
+                for c in value.chars() {
+                    if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}') {
+                        quoted.push(c);
+                    }
+                    quoted.push(c);
+                }
+                writeln!(out, "$env:{key} = '{quoted}'")?
+            }
+            Shell::Direnv => {
+                let comma = if std::mem::take(&mut first) { "" } else { "," };
+                write!(out, "{comma}\"{key}\":{}", json_string(value))?
+            }
+        }
+    }
+    if shell == Shell::Direnv {
+        writeln!(out, "}}")?;
+    }
+    Ok(())
+}
+
+/// Quotes a string for JSON
+pub(crate) fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Writes `(key, value)` pairs as a script for `shell` to a string, see [`write_shell`]
+pub fn to_shell_string<I, K, V>(shell: Shell, pairs: I) -> std::io::Result<String>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut out = vec![];
+    write_shell(&mut out, shell, pairs)?;
+    Ok(String::from_utf8(out).expect("only strings were written"))
+}
+
+/// Whether `key` is letters, digits and underscores, not starting with a digit
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Writes `(key, value)` pairs in the format of GitHub Actions' `$GITHUB_ENV` file
+///
+/// Single line values are written as `KEY=value`, values with line breaks use the `KEY<<DELIMITER` syntax,
+/// with a delimiter that isn't in the value. See [`append_github_env`] to add them to the current workflow.
+///
+/// ```rust
+/// let mut out = vec![];
+/// simple_env_load::write_github_env(&mut out, [("NAME", "app"), ("CERT", "line 1\nline 2")]).unwrap();
+///
+/// let out = String::from_utf8(out).unwrap();
+/// let (delimiter, _) = out.strip_prefix("NAME=app\nCERT<<").unwrap().split_once('\n').unwrap();
+/// assert!(out.ends_with(&format!("<<{delimiter}\nline 1\nline 2\n{delimiter}\n")));
+/// ```
+pub fn write_github_env<W, I, K, V>(mut out: W, pairs: I) -> std::io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    for (key, value) in pairs {
+        let (key, value) = (key.as_ref(), value.as_ref());
+        if !valid_key(key) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("'{key}' can't be written as a key"),
+            ));
+        }
+        if !value.contains(['\n', '\r']) {
+            writeln!(out, "{key}={value}")?;
+            continue;
+        }
+
+        let delimiter = loop {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .subsec_nanos();
+            let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let delimiter = format!("ghadelimiter_{}_{nanos:x}_{count}", std::process::id());
+            if !value.contains(&delimiter) {
+                break delimiter;
+            }
+        };
+        writeln!(out, "{key}<<{delimiter}\n{value}\n{delimiter}")?;
+    }
+    Ok(())
+}
+
+/// Appends `(key, value)` pairs to the file `$GITHUB_ENV` names, so the following steps of a GitHub Actions job have them
+///
+/// If `$GITHUB_ENV` isn't set, this is a [`NotFound`](std::io::ErrorKind::NotFound) error. See [`write_github_env`].
+///
+/// ```rust,no_run
+/// let report = simple_env_load::Loader::new().path(".env.ci").load().unwrap();
+/// simple_env_load::append_github_env(report.into_pairs()).unwrap();
+/// ```
+pub fn append_github_env<I, K, V>(pairs: I) -> std::io::Result<()>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let path = std::env::var_os("GITHUB_ENV")
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "GITHUB_ENV isn't set"))?;
+
+    let mut data = vec![];
+    write_github_env(&mut data, pairs)?;
+    std::fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)?
+        .write_all(&data)
+}
+
+/// Replaces the file at `path` with `data` atomically, so readers never see a partially written file
+///
+/// The data is written to a temporary file next to `path`, flushed to disk, then renamed over `path`.
+///
+/// On Unix, `mode` sets the permissions of the new file (e.g. `Some(0o600)` for secrets), and the
+/// temporary file is only readable by its owner while it's being written. Without a `mode`, an
+/// existing file keeps its permissions. On other platforms, `mode` is ignored.
+///
+/// ```rust
+/// # let path = std::env::temp_dir().join("write_file_atomic_doc.env");
+/// let data = simple_env_load::to_env_string([("API_TOKEN", "secret")]).unwrap();
+/// simple_env_load::write_file_atomic(&path, data, Some(0o600)).unwrap();
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn write_file_atomic(
+    path: impl AsRef<Path>,
+    data: impl AsRef<[u8]>,
+    mode: Option<u32>,
+) -> std::io::Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let path = path.as_ref();
+    let name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the path has no file name",
+        )
+    })?;
+    let tmp = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let existing = std::fs::metadata(path).ok().map(|meta| meta.permissions());
+    let result = (|| {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        if mode.is_some() || existing.is_some() {
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        }
+
+        let mut file = options.open(&tmp)?;
+        file.write_all(data.as_ref())?;
+        file.sync_all()?;
+
+        match (mode, existing) {
+            #[cfg(unix)]
+            (Some(mode), _) => {
+                use std::os::unix::fs::PermissionsExt as _;
+                file.set_permissions(std::fs::Permissions::from_mode(mode))?
+            }
+            (_, Some(permissions)) => file.set_permissions(permissions)?,
+            _ => {}
+        }
+        drop(file);
+
+        std::fs::rename(&tmp, path)?;
+
+        #[cfg(unix)]
+        if let Some(dir) = path.parent() {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            std::fs::File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+pub(crate) fn valid_key(key: &str) -> bool {
+    !key.is_empty() && !key.contains(|c: char| c.is_whitespace() || matches!(c, '=' | '#'))
+}
+
+/// Double quotes a value for systemd when it needs quotes, escaping the characters it escapes
+fn quote_systemd(value: &str) -> Cow<'_, str> {
+    let special = |c: char| c.is_control() || matches!(c, '"' | '\'' | '\\' | '`' | '$');
+    let padded = value.starts_with(char::is_whitespace) || value.ends_with(char::is_whitespace);
+    if !padded && !value.contains(special) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | '`' | '$') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    Cow::Owned(out)
+}
+
+/// Single quotes a value for a shell when it needs quotes, a `'` is written as `'\''`
+fn quote_posix(value: &str) -> Cow<'_, str> {
+    let plain = |c: char| {
+        c.is_ascii_alphanumeric()
+            || matches!(c, '_' | '-' | '.' | '/' | ':' | ',' | '@' | '+' | '%')
+    };
+    match !value.chars().all(plain) {
+        true => Cow::Owned(format!("'{}'", value.replace('\'', r"'\''"))),
+        false => Cow::Borrowed(value),
+    }
+}
+
+/// Quotes a value so that it parses back the same, quoting only when needed
+pub(crate) fn quote(value: &str) -> Cow<'_, str> {
+    let special = |c: char| c.is_whitespace() || matches!(c, '#' | '$' | '"' | '\'' | '\\' | '`');
+    if !value.contains(special) {
+        return Cow::Borrowed(value);
+    }
+
+    if !value.contains(|c: char| c == '\'' || c.is_control()) {
+        return Cow::Owned(format!("'{value}'"));
+    }
+
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\\' | '"' | '$' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    Cow::Owned(out)
+}
+
+#[test]
+fn write_round_trip() {
+    let pairs = [
+        ("PLAIN", "postgres://localhost/app"),
+        ("EMPTY", ""),
+        ("SPACES", "  padded  "),
+        ("HASH", "abc#123"),
+        ("DOLLAR", "$HOME"),
+        ("BOTH", "it's $HOME"),
+        ("MULTI", "a\r\nb\t\"c\"\\"),
+        ("CONTROL", "\u{7}"),
+        ("UNICODE", "h\u{e9}llo"),
+    ];
+    let data = to_env_string(pairs).unwrap();
+    let parsed = crate::parse_to_map(&data);
+    let parsed = parsed
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(parsed, pairs);
+
+    assert!(to_env_string([("BAD KEY", "1")]).is_err());
+    assert!(to_env_string([("", "1")]).is_err());
+}
+
+#[test]
+fn quote_every_value() {
+    const CHARS: [char; 7] = ['a', '"', '\'', '\\', ' ', '#', '\n'];
+    for len in 0..=5 {
+        for mut n in 0..CHARS.len().pow(len) {
+            let mut value = String::new();
+            for _ in 0..len {
+                value.push(CHARS[n % CHARS.len()]);
+                n /= CHARS.len();
+            }
+
+            let quoted = format!("K={}", quote(&value));
+            let entry = crate::parse::parse(&quoted, Default::default()).next();
+            assert_eq!(entry.map(|entry| entry.value), Some(Cow::from(&*value)));
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn write_shell_scripts() {
+    let value = "it's \"$HOME\" `id` \\n\nnext line";
+    let script = to_shell_string(Shell::Posix, [("SIMPLE_ENV_LOAD_SHELL", value)]).unwrap();
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(script + "printf %s \"$SIMPLE_ENV_LOAD_SHELL\"")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), value);
+
+    let script = to_shell_string(Shell::PowerShell, [("A", "\u{2019}")]).unwrap();
+    assert_eq!(script, "$env:A = '\u{2019}\u{2019}'\n");
+    assert!(to_shell_string(Shell::Fish, [("A-B", "1")]).is_err());
+
+    let script = to_shell_string(Shell::Direnv, [("A", "1"), ("B", "\"\n\u{1}")]).unwrap();
+    assert_eq!(script, r#"{"A":"1","B":"\"\n\u0001"}"#.to_string() + "\n");
+    assert_eq!(
+        to_shell_string(Shell::Direnv, [("", ""); 0]).unwrap(),
+        "{}\n"
+    );
+}
+
+#[test]
+fn write_dialect_round_trip() {
+    let pairs = [
+        ("PLAIN", "a b"),
+        ("PADDED", " a "),
+        ("QUOTES", "it's \"quoted\""),
+        ("ESCAPES", "\\n `id` $HOME"),
+        ("MULTI", "line 1\nline 2\\"),
+        ("COMMENT", "#not ;a comment"),
+        ("EMPTY", ""),
+    ];
+    for dialect in [
+        Dialect::Systemd,
+        Dialect::Posix,
+        Dialect::Ruby,
+        Dialect::Compose,
+    ] {
+        let data = to_env_string_as(dialect, pairs).unwrap();
+        let options = crate::parse::Options {
+            dialect,
+            ..Default::default()
+        };
+        let parsed = crate::parse::parse(&data, options)
+            .map(|entry| (entry.key, entry.value.into_owned()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parsed,
+            pairs.map(|(k, v)| (k, v.to_string())),
+            "{dialect:?}"
+        );
+    }
+    let data = to_env_string_as(Dialect::Posix, [("A", "it's"), ("B", "plain")]).unwrap();
+    assert_eq!(data, "A='it'\\''s'\nB=plain\n");
+
+    let data = to_env_string_as(Dialect::DockerCompose, [("A", " 'b' # c")]).unwrap();
+    assert_eq!(data, "A= 'b' # c\n");
+    assert!(to_env_string_as(Dialect::DockerCompose, [("A", "b\nc")]).is_err());
+}
+
+#[test]
+fn write_github_env_values() {
+    let mut out = vec![];
+    write_github_env(&mut out, [("A", "1"), ("B", "x\r\ny"), ("C", "")]).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines = out.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "A=1");
+    let delimiter = lines[1].strip_prefix("B<<").unwrap();
+    assert_eq!(lines[2..], ["x", "y", delimiter, "C="]);
+
+    let parsed = crate::parse::parse(&out, Default::default());
+    let values = parsed.map(|entry| entry.value.into_owned());
+    assert_eq!(values.collect::<Vec<_>>(), ["1", "x\r\ny", ""]);
+    assert!(write_github_env(vec![], [("A=B", "1")]).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn write_atomic_permissions() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let path = std::env::temp_dir().join("simple_env_load_atomic.env");
+    let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+    write_file_atomic(&path, "A=1\n", Some(0o600)).unwrap();
+    assert_eq!(mode(&path), 0o600);
+
+    write_file_atomic(&path, "A=2\n", None).unwrap();
+    assert_eq!(mode(&path), 0o600);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "A=2\n");
+
+    std::fs::remove_file(&path).unwrap();
+}